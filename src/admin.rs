@@ -0,0 +1,259 @@
+use poise::serenity_prelude as serenity;
+use poise::ChoiceParameter;
+use std::time::Duration;
+use crate::{analytics, datasets, dex, settings, Context, Error};
+use crate::dex::{reply, reply_error};
+
+/// Number of Pokemon, commands and buttons listed by `/admin stats`.
+const STATS_LIMIT: u32 = 10;
+
+/// Bot maintenance and permission commands.
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands(
+        "reload",
+        "default",
+        "stats",
+        "block_user",
+        "unblock_user",
+        "block_channel",
+        "allow_channel",
+        "admin_role",
+        "register"
+    ),
+    subcommand_required
+)]
+pub async fn admin(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Reload a dataset's database file, keeping the current one if the new one fails validation.
+#[poise::command(slash_command, owners_only)]
+pub async fn reload(
+    ctx: Context<'_>,
+    #[description = "Dataset to reload (defaults to all of them)."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let names: Vec<String> = match dataset {
+        Some(dataset) => match ctx.data().datasets.get(&dataset) {
+            Some(dataset) => vec![dataset.name.clone()],
+            None => return reply_error(ctx, datasets::unknown(&ctx.data().datasets, &dataset)).await,
+        },
+        None => ctx.data().datasets.all().iter().map(|dataset| dataset.name.clone()).collect(),
+    };
+    // Validating a database takes a while.
+    ctx.defer_ephemeral().await?;
+    let mut lines: Vec<String> = Vec::new();
+    for name in names {
+        let datasets = ctx.data().datasets.clone();
+        let line = match tokio::task::spawn_blocking(move || datasets.reload(&name)).await? {
+            Ok(summary) => {
+                tracing::info!("{}", summary);
+                summary
+            }
+            Err(e) => {
+                tracing::warn!("{}", e);
+                e.to_string()
+            }
+        };
+        lines.push(line);
+    }
+    let mut content = lines.join("\n");
+    if content.len() > 2000 {
+        content.truncate(content.floor_char_boundary(1997));
+        content.push_str("...");
+    }
+    reply(ctx, content).await
+}
+
+/// Set the dataset used where no server or channel default is set.
+#[poise::command(slash_command, owners_only)]
+pub async fn default(
+    ctx: Context<'_>,
+    #[description = "Dataset to use by default (defaults to the one in datasets.toml)."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    match ctx.data().datasets.set_default(dataset.as_deref()) {
+        Ok(dataset) => reply(ctx, format!("The bot now uses {} by default.", dataset.title)).await,
+        Err(e) => reply_error(ctx, e.to_string()).await,
+    }
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum Period {
+    #[name = "Last day"]
+    Day,
+    #[name = "Last week"]
+    Week,
+}
+
+/// Show how the bot is doing and what it is used for.
+#[poise::command(slash_command, owners_only)]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "Period to show usage for (defaults to the last day)."] period: Option<Period>,
+) -> Result<(), Error> {
+    let period = period.unwrap_or(Period::Day);
+    let (blocked_users, blocked_channels) = settings::count_blocked()?;
+    let uptime = ctx.data().started.elapsed().as_secs();
+    let datasets = ctx.data().datasets.all();
+    let report = analytics::report(
+        Duration::from_secs(match period {
+            Period::Day => 86400,
+            Period::Week => 7 * 86400,
+        }),
+        STATS_LIMIT,
+    )?;
+    let mut content = format!(
+        "Servers: {}\nUptime: {}d {}h {}m\nDatasets: {} (default: {})\nBlocked users: {}\nBlocked channels: {}\n",
+        ctx.cache().guild_count(),
+        uptime / 86400,
+        uptime % 86400 / 3600,
+        uptime % 3600 / 60,
+        datasets
+            .iter()
+            .map(|dataset| dataset.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        ctx.data().datasets.default().name,
+        blocked_users,
+        blocked_channels,
+    );
+    content.push_str(&format!(
+        "\n**{}**\nUses: {}, errors: {} ({:.1}%)\n",
+        period.name(),
+        report.events,
+        report.errors,
+        percentage(report.errors, report.events),
+    ));
+    content.push_str("\nTop Pokemon:\n");
+    for lookups in &report.pokemon {
+        let name = lookups
+            .dataset
+            .as_deref()
+            .and_then(|dataset| ctx.data().datasets.get(dataset))
+            .and_then(|dataset| dex::get_pokemon_by_id(&dataset, &lookups.pokemon_id).ok())
+            .map_or_else(|| format!("#{}", lookups.pokemon_id), |pokemon| pokemon.name);
+        content.push_str(&format!("{}: {}\n", name, lookups.lookups));
+    }
+    for (title, usages) in [("Top commands", &report.commands), ("Top buttons", &report.buttons)] {
+        content.push_str(&format!("\n{}:\n", title));
+        for usage in usages {
+            content.push_str(&format!(
+                "{}: {} uses, {:.1}% errors, {} ms average\n",
+                usage.name,
+                usage.uses,
+                percentage(usage.errors, usage.uses),
+                usage.average_ms.unwrap_or(0),
+            ));
+        }
+    }
+    reply(ctx, content).await
+}
+
+fn percentage(part: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / total as f64
+    }
+}
+
+/// Stop a user from using the bot anywhere.
+#[poise::command(slash_command, owners_only, rename = "block-user")]
+pub async fn block_user(
+    ctx: Context<'_>,
+    #[description = "User to block."] user: serenity::User,
+    #[description = "Why they are blocked, for other owners."] reason: Option<String>,
+) -> Result<(), Error> {
+    if settings::block_user(user.id, reason.as_deref())? {
+        reply(ctx, format!("Blocked {}.", user.name)).await
+    } else {
+        reply_error(ctx, format!("{} is already blocked.", user.name)).await
+    }
+}
+
+/// Let a blocked user use the bot again.
+#[poise::command(slash_command, owners_only, rename = "unblock-user")]
+pub async fn unblock_user(
+    ctx: Context<'_>,
+    #[description = "User to unblock."] user: serenity::User,
+) -> Result<(), Error> {
+    if settings::unblock_user(user.id)? {
+        reply(ctx, format!("Unblocked {}.", user.name)).await
+    } else {
+        reply_error(ctx, format!("{} is not blocked.", user.name)).await
+    }
+}
+
+/// Stop the bot from being used in a channel.
+#[poise::command(slash_command, guild_only, check = "crate::permissions::guild_admin", rename = "block-channel")]
+pub async fn block_channel(
+    ctx: Context<'_>,
+    #[description = "Channel to block (defaults to this one)."]
+    #[channel_types("Text", "News", "PublicThread", "PrivateThread", "NewsThread")]
+    channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let channel_id = channel.map_or(ctx.channel_id(), |channel| channel.id);
+    if settings::block_channel(ctx.guild_id(), channel_id)? {
+        reply(ctx, format!("The bot can no longer be used in <#{}>.", channel_id)).await
+    } else {
+        reply_error(ctx, format!("<#{}> is already blocked.", channel_id)).await
+    }
+}
+
+/// Let the bot be used in a blocked channel again.
+#[poise::command(slash_command, guild_only, check = "crate::permissions::guild_admin", rename = "allow-channel")]
+pub async fn allow_channel(
+    ctx: Context<'_>,
+    #[description = "Channel to allow (defaults to this one)."]
+    #[channel_types("Text", "News", "PublicThread", "PrivateThread", "NewsThread")]
+    channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let channel_id = channel.map_or(ctx.channel_id(), |channel| channel.id);
+    if settings::allow_channel(channel_id)? {
+        reply(ctx, format!("The bot can be used in <#{}> again.", channel_id)).await
+    } else {
+        reply_error(ctx, format!("<#{}> is not blocked.", channel_id)).await
+    }
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum RoleAction {
+    #[name = "Add"]
+    Add,
+    #[name = "Remove"]
+    Remove,
+}
+
+/// Add or remove a role whose members can manage the bot in this server.
+#[poise::command(slash_command, guild_only, required_permissions = "MANAGE_GUILD", rename = "admin-role")]
+pub async fn admin_role(
+    ctx: Context<'_>,
+    #[description = "Whether to add or remove the role."] action: RoleAction,
+    #[description = "Role to add or remove."] role: serenity::Role,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    match action {
+        RoleAction::Add if settings::add_admin_role(guild_id, role.id)? => {
+            reply(ctx, format!("Members of {} can now manage the bot in this server.", role.name)).await
+        }
+        RoleAction::Add => reply_error(ctx, format!("{} already is an admin role.", role.name)).await,
+        RoleAction::Remove if settings::remove_admin_role(guild_id, role.id)? => {
+            reply(ctx, format!("{} is no longer an admin role.", role.name)).await
+        }
+        RoleAction::Remove => reply_error(ctx, format!("{} is not an admin role.", role.name)).await,
+    }
+}
+
+/// Register or unregister the slash commands, globally or in this server.
+///
+/// Also a prefix command, as it is needed to get the slash commands back after unregistering them.
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn register(ctx: Context<'_>) -> Result<(), Error> {
+    poise::builtins::register_application_commands_buttons(ctx).await?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+//! Usage analytics kept in analytics.db: every command and dex button use, with the Pokemon it was about,
+//! how long it took and whether it failed. `/admin stats` reports on them.
+
+use poise::serenity_prelude as serenity;
+use std::time::{Duration, Instant, SystemTime};
+use crate::config;
+use crate::datasets::Dataset;
+use crate::dex::read_query;
+use crate::migrations::Migrations;
+use crate::Context;
+
+pub const MIGRATIONS: Migrations = Migrations {
+    versions: &[(1, "Events", include_str!("analytics/migrations/0001_events.sql"))],
+    baseline_table: None,
+};
+
+pub fn open() -> rusqlite::Connection {
+    rusqlite::Connection::open(&config::get().database.analytics).unwrap()
+}
+
+pub struct Event<'a> {
+    /// "command" or "button".
+    pub kind: &'a str,
+    /// Qualified command name, or the button's custom id prefix.
+    pub name: &'a str,
+    pub guild_id: Option<serenity::GuildId>,
+    pub dataset: Option<&'a str>,
+    pub pokemon_id: Option<u16>,
+    pub duration_ms: u64,
+    pub success: bool,
+}
+
+/// Records an event, unless analytics are turned off. Failing to do so is only logged, it must never fail the command.
+pub fn record(event: &Event) {
+    if !config::get().features.analytics {
+        return;
+    }
+    let result = open().execute(
+        &read_query("./src/analytics/queries/record_event.sql"),
+        rusqlite::params![
+            event.kind,
+            event.name,
+            event.guild_id.map(|guild_id| guild_id.get() as i64),
+            event.dataset,
+            event.pokemon_id,
+            event.duration_ms as i64,
+            event.success,
+        ],
+    );
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "Failed to record analytics event");
+    }
+}
+
+/// What is known about the running command, kept in its invocation data.
+struct Invocation {
+    started: Instant,
+    dataset: Option<String>,
+    pokemon_id: Option<u16>,
+}
+
+/// Called before every command.
+pub async fn start(ctx: Context<'_>) {
+    ctx.set_invocation_data(Invocation {
+        started: Instant::now(),
+        dataset: None,
+        pokemon_id: None,
+    })
+    .await;
+}
+
+/// Records which Pokemon the running command looked up.
+pub async fn target(ctx: Context<'_>, dataset: &Dataset, pokemon_id: u16) {
+    if let Some(mut invocation) = ctx.invocation_data::<Invocation>().await {
+        invocation.dataset = Some(dataset.name.clone());
+        invocation.pokemon_id = Some(pokemon_id);
+    }
+}
+
+/// Records the command that just finished and returns how long it took.
+pub async fn finish(ctx: Context<'_>, success: bool) -> Option<u64> {
+    let (duration_ms, dataset, pokemon_id) = {
+        let invocation = ctx.invocation_data::<Invocation>().await?;
+        (
+            invocation.started.elapsed().as_millis() as u64,
+            invocation.dataset.clone(),
+            invocation.pokemon_id,
+        )
+    };
+    record(&Event {
+        kind: "command",
+        name: &ctx.command().qualified_name,
+        guild_id: ctx.guild_id(),
+        dataset: dataset.as_deref(),
+        pokemon_id,
+        duration_ms,
+        success,
+    });
+    Some(duration_ms)
+}
+
+pub struct Usage {
+    pub name: String,
+    pub uses: u32,
+    pub errors: u32,
+    pub average_ms: Option<u64>,
+}
+
+pub struct Lookups {
+    pub dataset: Option<String>,
+    pub pokemon_id: u16,
+    pub lookups: u32,
+}
+
+pub struct Report {
+    pub events: u32,
+    pub errors: u32,
+    pub pokemon: Vec<Lookups>,
+    pub commands: Vec<Usage>,
+    pub buttons: Vec<Usage>,
+}
+
+/// The most looked up Pokemon and most used commands and buttons over the last `period`.
+pub fn report(period: Duration, limit: u32) -> Result<Report, rusqlite::Error> {
+    let since = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .saturating_sub(period)
+        .as_secs() as i64;
+    let conn = open();
+    let (events, errors) = conn.query_row(&read_query("./src/analytics/queries/totals.sql"), [since], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    let pokemon = conn
+        .prepare(&read_query("./src/analytics/queries/top_pokemon.sql"))?
+        .query_map(rusqlite::params![since, limit], |row| {
+            Ok(Lookups {
+                dataset: row.get(0)?,
+                pokemon_id: row.get(1)?,
+                lookups: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<Lookups>, _>>()?;
+    let usage = |kind: &str| -> Result<Vec<Usage>, rusqlite::Error> {
+        conn.prepare(&read_query("./src/analytics/queries/top_names.sql"))?
+            .query_map(rusqlite::params![since, kind, limit], |row| {
+                Ok(Usage {
+                    name: row.get(0)?,
+                    uses: row.get(1)?,
+                    errors: row.get(2)?,
+                    average_ms: row.get(3)?,
+                })
+            })?
+            .collect()
+    };
+    let commands = usage("command")?;
+    let buttons = usage("button")?;
+    Ok(Report {
+        events,
+        errors,
+        pokemon,
+        commands,
+        buttons,
+    })
+}
@@ -0,0 +1,12 @@
+create table events (
+    id integer primary key not null,
+    occurred_at integer not null default (unixepoch()),
+    kind text not null,
+    name text not null,
+    guild_id integer,
+    dataset text,
+    pokemon_id integer,
+    duration_ms integer,
+    success integer not null
+);
+create index events_occurred_at on events (occurred_at);
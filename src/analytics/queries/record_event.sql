@@ -0,0 +1,4 @@
+insert into events
+    (kind, name, guild_id, dataset, pokemon_id, duration_ms, success)
+values
+    (?1, ?2, ?3, ?4, ?5, ?6, ?7)
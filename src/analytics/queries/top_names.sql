@@ -0,0 +1,17 @@
+select
+    name,
+    count(*) as uses,
+    sum(not success) as errors,
+    cast(avg(duration_ms) as integer) as average_ms
+from
+    events
+where
+    occurred_at >= ?1
+    and kind = ?2
+group by
+    name
+order by
+    uses desc,
+    name
+limit
+    ?3
@@ -0,0 +1,17 @@
+select
+    dataset,
+    pokemon_id,
+    count(*) as lookups
+from
+    events
+where
+    occurred_at >= ?1
+    and pokemon_id is not null
+group by
+    dataset,
+    pokemon_id
+order by
+    lookups desc,
+    pokemon_id
+limit
+    ?2
@@ -0,0 +1,7 @@
+select
+    count(*),
+    coalesce(sum(not success), 0)
+from
+    events
+where
+    occurred_at >= ?1
@@ -0,0 +1,342 @@
+pub mod damage;
+
+use poise::serenity_prelude as serenity;
+use crate::{Context, Error};
+use crate::datasets::{self, Dataset};
+use crate::dex::{self, autocomplete, reply_error, Pokemon};
+use crate::dex::stats::{calc_stats, Nature, Spread, Stat};
+use damage::{Ability, Combatant, DamageResult, Item, Move, Split};
+use futures::{Stream, StreamExt};
+
+/// Calculate the damage one Pokemon's move deals to another.
+///
+/// Usage:
+/// /calc Garchomp Heatran Earthquake attacker_evs:252 Atk attacker_nature:Adamant
+#[poise::command(slash_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn calc(
+    ctx: Context<'_>,
+    #[description = "Attacking Pokemon."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    attacker: String,
+    #[description = "Defending Pokemon."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    defender: String,
+    #[description = "Move used by the attacker."]
+    #[autocomplete = "autocomplete::autocomplete_move"]
+    #[rename = "move"]
+    move_name: String,
+    #[description = "Attacker's level (defaults to 100)."]
+    #[min = 1]
+    #[max = 100]
+    attacker_level: Option<u8>,
+    #[description = "Attacker's EVs, e.g. 252 Atk / 4 HP (unlisted EVs are 0)."]
+    attacker_evs: Option<String>,
+    #[description = "Attacker's IVs, e.g. 0 Atk (unlisted IVs are 31)."]
+    attacker_ivs: Option<String>,
+    #[description = "Attacker's nature (defaults to a neutral nature)."]
+    attacker_nature: Option<Nature>,
+    #[description = "Attacker's held item."]
+    #[autocomplete = "autocomplete_item"]
+    attacker_item: Option<String>,
+    #[description = "Attacker's ability."]
+    #[autocomplete = "autocomplete_ability"]
+    attacker_ability: Option<String>,
+    #[description = "Attacker's stat stage for the move's attacking stat."]
+    #[min = -6]
+    #[max = 6]
+    attacker_boost: Option<i8>,
+    #[description = "Defender's level (defaults to 100)."]
+    #[min = 1]
+    #[max = 100]
+    defender_level: Option<u8>,
+    #[description = "Defender's EVs, e.g. 252 HP / 252 Def (unlisted EVs are 0)."]
+    defender_evs: Option<String>,
+    #[description = "Defender's IVs, e.g. 0 Def (unlisted IVs are 31)."]
+    defender_ivs: Option<String>,
+    #[description = "Defender's nature (defaults to a neutral nature)."]
+    defender_nature: Option<Nature>,
+    #[description = "Defender's held item."]
+    #[autocomplete = "autocomplete_item"]
+    defender_item: Option<String>,
+    #[description = "Defender's ability."]
+    #[autocomplete = "autocomplete_ability"]
+    defender_ability: Option<String>,
+    #[description = "Defender's stat stage for the move's defending stat."]
+    #[min = -6]
+    #[max = 6]
+    defender_boost: Option<i8>,
+    #[description = "Whether the move lands a critical hit."]
+    critical: Option<bool>,
+    #[description = "Game dataset to use."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let Some(attacker_pokemon) = dex::resolve_pokemon(&dataset, &attacker)? else {
+        return reply_error(ctx, dex::unknown_pokemon(&attacker)).await;
+    };
+    let Some(defender_pokemon) = dex::resolve_pokemon(&dataset, &defender)? else {
+        return reply_error(ctx, dex::unknown_pokemon(&defender)).await;
+    };
+    let Some(attack) = resolve_move(&dataset, &move_name)? else {
+        return reply_error(ctx, format!("Could not find the move \"{}\".", move_name)).await;
+    };
+    if attack.split == Split::Status {
+        return reply_error(ctx, format!("{} is a status move.", attack.name)).await;
+    }
+    let (attack_stat, defense_stat) = match attack.split {
+        Split::Physical => (Stat::Atk, Stat::Def),
+        _ => (Stat::Spa, Stat::Spd),
+    };
+
+    let attacker_spread = match Spread::from_options(
+        attacker_level,
+        attacker_ivs.as_deref(),
+        attacker_evs.as_deref(),
+        attacker_nature,
+    ) {
+        Ok(spread) => spread,
+        Err(message) => return reply_error(ctx, format!("Attacker: {}", message)).await,
+    };
+    let defender_spread = match Spread::from_options(
+        defender_level,
+        defender_ivs.as_deref(),
+        defender_evs.as_deref(),
+        defender_nature,
+    ) {
+        Ok(spread) => spread,
+        Err(message) => return reply_error(ctx, format!("Defender: {}", message)).await,
+    };
+    let attacker_combatant = match combatant(
+        &attacker_pokemon,
+        &attacker_spread,
+        attack_stat,
+        attacker_boost,
+        attacker_item.as_deref(),
+        attacker_ability.as_deref(),
+    ) {
+        Ok(combatant) => combatant,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let defender_combatant = match combatant(
+        &defender_pokemon,
+        &defender_spread,
+        defense_stat,
+        defender_boost,
+        defender_item.as_deref(),
+        defender_ability.as_deref(),
+    ) {
+        Ok(combatant) => combatant,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+
+    let effectiveness = get_move_effectiveness(&dataset, &defender_pokemon.id, &attack.type_name)?;
+    let critical = critical.unwrap_or(false);
+    let result = damage::calculate(
+        &attacker_combatant,
+        &defender_combatant,
+        &attack,
+        effectiveness,
+        critical,
+    );
+
+    let description = format!(
+        "{0} {1}{2} vs. {3}{4}: {5}-{6} ({7:.1} - {8:.1}%) -- {9}",
+        describe_side(&attacker_combatant, &attacker_spread, attack_stat, false),
+        attack.name,
+        if critical { " on a critical hit" } else { "" },
+        describe_side(&defender_combatant, &defender_spread, defense_stat, true),
+        if effectiveness == 1.0 {
+            "".to_owned()
+        } else {
+            format!(" ({}x)", effectiveness)
+        },
+        result.min(),
+        result.max(),
+        result.percent(result.min()),
+        result.percent(result.max()),
+        describe_ko_chance(&result)
+    );
+    let rolls = result
+        .rolls
+        .iter()
+        .map(|roll| roll.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    ctx.send(
+        poise::CreateReply::default().ephemeral(true).embed(
+            serenity::CreateEmbed::new()
+                .title(format!(
+                    "{0} vs. {1}",
+                    attacker_combatant.name, defender_combatant.name
+                ))
+                .colour(dex::get_color_from_type(&attack.type_name))
+                .description(description)
+                .field("Possible damage amounts", format!("```c\n{}```", rolls), false),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+fn combatant(
+    pokemon: &Pokemon,
+    spread: &Spread,
+    boosted_stat: Stat,
+    boost: Option<i8>,
+    item: Option<&str>,
+    ability: Option<&str>,
+) -> Result<Combatant, String> {
+    let item = item
+        .map(|name| {
+            Item::from_name(name).ok_or_else(|| {
+                format!("{} is not an item the calculator knows about.", name)
+            })
+        })
+        .transpose()?;
+    let ability = ability
+        .map(|name| {
+            Ability::from_name(name).ok_or_else(|| {
+                format!("{} is not an ability the calculator knows about.", name)
+            })
+        })
+        .transpose()?;
+    let mut boosts = [0i8; 6];
+    if let Some(index) = boosted_stat.index() {
+        boosts[index] = boost.unwrap_or(0);
+    }
+    Ok(Combatant {
+        name: pokemon.name.clone(),
+        level: spread.level,
+        types: [&pokemon.type1_name, &pokemon.type2_name]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect(),
+        stats: calc_stats(pokemon, spread),
+        boosts,
+        ability,
+        item,
+    })
+}
+
+/// Summarizes one side of the calculation, Showdown style, e.g. "+1 252+ Atk Choice Band Garchomp".
+fn describe_side(
+    combatant: &Combatant,
+    spread: &Spread,
+    stat: Stat,
+    defending: bool,
+) -> String {
+    let index = stat.index().expect("BST is not a battle stat");
+    let mut parts: Vec<String> = Vec::new();
+    match combatant.boosts[index] {
+        0 => {}
+        boost if boost > 0 => parts.push(format!("+{}", boost)),
+        boost => parts.push(boost.to_string()),
+    }
+    let nature_sign = match spread.nature.multiplier(stat) {
+        110 => "+",
+        90 => "-",
+        _ => "",
+    };
+    let stat_evs = format!("{0}{1} {2}", spread.evs[index], nature_sign, stat.short_name());
+    if defending {
+        parts.push(format!("{} HP /", spread.evs[0]));
+    }
+    parts.push(stat_evs);
+    if let Some(item) = combatant.item {
+        parts.push(item.name().to_owned());
+    }
+    if let Some(ability) = combatant.ability {
+        parts.push(ability.name().to_owned());
+    }
+    if spread.level != 100 {
+        parts.push(format!("Lv. {}", spread.level));
+    }
+    parts.push(combatant.name.clone());
+    parts.join(" ")
+}
+
+fn describe_ko_chance(result: &DamageResult) -> String {
+    match result.ko_chance() {
+        None if result.max() == 0 => "no damage".to_owned(),
+        None => "possibly the worst move ever".to_owned(),
+        Some((hits, chance)) => {
+            let ko = if hits == 1 {
+                "OHKO".to_owned()
+            } else {
+                format!("{}HKO", hits)
+            };
+            if chance >= 1.0 {
+                format!("guaranteed {}", ko)
+            } else {
+                format!("{0:.1}% chance to {1}", chance * 100.0, ko)
+            }
+        }
+    }
+}
+
+fn resolve_move(dataset: &Dataset, name_or_id: &str) -> Result<Option<Move>, rusqlite::Error> {
+    // Like pokemon, autocompleted moves are passed in by id, and typed out moves by name.
+    let path = match name_or_id.parse::<u16>() {
+        Ok(_) => "./src/calc/queries/get_move.sql",
+        Err(_) => "./src/calc/queries/get_move_by_name.sql",
+    };
+    let sql = crate::dex::read_query(path);
+    let _timer = crate::metrics::time_query(path);
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    let result = stmt.query_row([name_or_id], |row| {
+        let split_name: String = row.get(3)?;
+        Ok(Move {
+            name: row.get(1)?,
+            type_name: row.get(2)?,
+            split: Split::from_name(&split_name).unwrap_or(Split::Status),
+            power: row.get::<_, Option<u16>>(4)?.unwrap_or(0),
+        })
+    });
+    match result {
+        Ok(m) => Ok(Some(m)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn get_move_effectiveness(dataset: &Dataset, pokemon_id: &u16, type_name: &str) -> Result<f32, rusqlite::Error> {
+    let sql = crate::dex::read_query("./src/calc/queries/get_move_effectiveness.sql");
+    let _timer = crate::metrics::time_query("./src/calc/queries/get_move_effectiveness.sql");
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    stmt.query_row(rusqlite::params![pokemon_id, type_name], |row| row.get(0))
+        .or_else(|err| match err {
+            // The None pokemon has no types to be effective against.
+            rusqlite::Error::QueryReturnedNoRows => Ok(1.0),
+            _ => Err(err),
+        })
+}
+
+async fn autocomplete_item<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    futures::stream::iter(Item::ALL)
+        .filter(move |item| {
+            futures::future::ready(item.name().to_lowercase().starts_with(&partial.to_lowercase()))
+        })
+        .map(|item| serenity::AutocompleteChoice::new(item.name(), item.name()))
+}
+
+async fn autocomplete_ability<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    futures::stream::iter(Ability::ALL)
+        .filter(move |ability| {
+            futures::future::ready(ability.name().to_lowercase().starts_with(&partial.to_lowercase()))
+        })
+        .map(|ability| serenity::AutocompleteChoice::new(ability.name(), ability.name()))
+}
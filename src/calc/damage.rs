@@ -0,0 +1,479 @@
+//! Gen 5+ damage formula, following the rounding rules of the Pokemon Showdown damage calculator.
+//!
+//! Nothing in here touches Discord or the database, so the numbers can be checked in isolation.
+
+use crate::dex::stats::Stat;
+
+/// Modifiers are fixed point numbers where 4096 is 1x.
+const NEUTRAL_MODIFIER: u32 = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Split {
+    Physical,
+    Special,
+    Status,
+}
+
+impl Split {
+    /// Parses a name from the splits table.
+    pub fn from_name(name: &str) -> Option<Split> {
+        match name {
+            "Physical" => Some(Split::Physical),
+            "Special" => Some(Split::Special),
+            "Status" => Some(Split::Status),
+            _ => None,
+        }
+    }
+}
+
+/// Held items that change the damage formula.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item {
+    ChoiceBand,
+    ChoiceSpecs,
+    LifeOrb,
+    ExpertBelt,
+    AssaultVest,
+    Eviolite,
+}
+
+impl Item {
+    pub const ALL: [Item; 6] = [
+        Item::ChoiceBand,
+        Item::ChoiceSpecs,
+        Item::LifeOrb,
+        Item::ExpertBelt,
+        Item::AssaultVest,
+        Item::Eviolite,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::ChoiceBand => "Choice Band",
+            Item::ChoiceSpecs => "Choice Specs",
+            Item::LifeOrb => "Life Orb",
+            Item::ExpertBelt => "Expert Belt",
+            Item::AssaultVest => "Assault Vest",
+            Item::Eviolite => "Eviolite",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Item> {
+        Item::ALL
+            .into_iter()
+            .find(|item| item.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Abilities that change the damage formula.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ability {
+    Adaptability,
+    Filter,
+    HugePower,
+    Hustle,
+    Levitate,
+    Multiscale,
+    PrismArmor,
+    PurePower,
+    SolidRock,
+    Technician,
+    ThickFat,
+    TintedLens,
+}
+
+impl Ability {
+    pub const ALL: [Ability; 12] = [
+        Ability::Adaptability,
+        Ability::Filter,
+        Ability::HugePower,
+        Ability::Hustle,
+        Ability::Levitate,
+        Ability::Multiscale,
+        Ability::PrismArmor,
+        Ability::PurePower,
+        Ability::SolidRock,
+        Ability::Technician,
+        Ability::ThickFat,
+        Ability::TintedLens,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ability::Adaptability => "Adaptability",
+            Ability::Filter => "Filter",
+            Ability::HugePower => "Huge Power",
+            Ability::Hustle => "Hustle",
+            Ability::Levitate => "Levitate",
+            Ability::Multiscale => "Multiscale",
+            Ability::PrismArmor => "Prism Armor",
+            Ability::PurePower => "Pure Power",
+            Ability::SolidRock => "Solid Rock",
+            Ability::Technician => "Technician",
+            Ability::ThickFat => "Thick Fat",
+            Ability::TintedLens => "Tinted Lens",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Ability> {
+        Ability::ALL
+            .into_iter()
+            .find(|ability| ability.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+pub struct Move {
+    pub name: String,
+    pub type_name: String,
+    pub split: Split,
+    pub power: u16,
+}
+
+/// One side of the calculation. `stats` are final stats, before stat stage boosts.
+pub struct Combatant {
+    pub name: String,
+    pub level: u8,
+    pub types: Vec<String>,
+    pub stats: [u16; 6],
+    pub boosts: [i8; 6],
+    pub ability: Option<Ability>,
+    pub item: Option<Item>,
+}
+
+impl Combatant {
+    fn stat(&self, stat: Stat) -> u16 {
+        self.stats[stat.index().expect("BST is not a battle stat")]
+    }
+
+    fn boost(&self, stat: Stat) -> i8 {
+        self.boosts[stat.index().expect("BST is not a battle stat")]
+    }
+
+    fn has_ability(&self, ability: Ability) -> bool {
+        self.ability == Some(ability)
+    }
+
+    fn has_item(&self, item: Item) -> bool {
+        self.item == Some(item)
+    }
+}
+
+pub struct DamageResult {
+    /// Every damage roll, from the lowest (85%) to the highest (100%) random factor.
+    pub rolls: [u16; 16],
+    pub defender_hp: u16,
+}
+
+impl DamageResult {
+    pub fn min(&self) -> u16 {
+        self.rolls[0]
+    }
+
+    pub fn max(&self) -> u16 {
+        self.rolls[15]
+    }
+
+    /// Damage as a percentage of the defender's max HP.
+    pub fn percent(&self, damage: u16) -> f64 {
+        f64::from(damage) * 100.0 / f64::from(self.defender_hp)
+    }
+
+    /// The fewest hits that can KO (up to 4) and the chance of doing so in that many hits.
+    pub fn ko_chance(&self) -> Option<(u8, f64)> {
+        if self.max() == 0 {
+            return None;
+        }
+        let hp = usize::from(self.defender_hp);
+        // Number of roll combinations reaching each total damage, capped at the defender's HP.
+        let mut totals: Vec<u64> = vec![0; hp + 1];
+        totals[0] = 1;
+        let mut combinations: u64 = 1;
+        for hits in 1..=4u8 {
+            let mut next: Vec<u64> = vec![0; hp + 1];
+            for (damage, count) in totals.iter().enumerate().filter(|(_, count)| **count > 0) {
+                for roll in self.rolls {
+                    next[(damage + usize::from(roll)).min(hp)] += count;
+                }
+            }
+            totals = next;
+            combinations *= self.rolls.len() as u64;
+            if totals[hp] > 0 {
+                return Some((hits, totals[hp] as f64 / combinations as f64));
+            }
+        }
+        None
+    }
+}
+
+/// Rounds to the nearest integer, with halves rounded down.
+fn poke_round(value: f64) -> u32 {
+    if value.fract() > 0.5 {
+        value.ceil() as u32
+    } else {
+        value.floor() as u32
+    }
+}
+
+fn chain_modifiers(modifiers: &[u32]) -> u32 {
+    modifiers
+        .iter()
+        .fold(NEUTRAL_MODIFIER, |chained, modifier| (chained * modifier + 2048) >> 12)
+}
+
+fn apply_modifier(value: u32, modifier: u32) -> u32 {
+    poke_round(f64::from(value) * f64::from(modifier) / f64::from(NEUTRAL_MODIFIER))
+}
+
+fn apply_boost(stat: u16, boost: i8) -> u32 {
+    let boost = i32::from(boost.clamp(-6, 6));
+    let stat = i32::from(stat);
+    let boosted = if boost >= 0 {
+        stat * (2 + boost) / 2
+    } else {
+        stat * 2 / (2 - boost)
+    };
+    boosted as u32
+}
+
+/// Calculates every damage roll of `attack` against `defender`.
+/// `effectiveness` is the type effectiveness multiplier of the move against the defender's types.
+pub fn calculate(
+    attacker: &Combatant,
+    defender: &Combatant,
+    attack: &Move,
+    effectiveness: f32,
+    critical: bool,
+) -> DamageResult {
+    let defender_hp = defender.stat(Stat::Hp);
+    let immune = attack.type_name == "Ground" && defender.has_ability(Ability::Levitate);
+    if attack.split == Split::Status || attack.power == 0 || effectiveness == 0.0 || immune {
+        return DamageResult {
+            rolls: [0; 16],
+            defender_hp,
+        };
+    }
+    let physical = attack.split == Split::Physical;
+    let (attack_stat, defense_stat) = if physical {
+        (Stat::Atk, Stat::Def)
+    } else {
+        (Stat::Spa, Stat::Spd)
+    };
+
+    let mut power_modifiers: Vec<u32> = Vec::new();
+    if attacker.has_ability(Ability::Technician) && attack.power <= 60 {
+        power_modifiers.push(6144);
+    }
+    let power = apply_modifier(attack.power.into(), chain_modifiers(&power_modifiers)).max(1);
+
+    // Critical hits ignore the attacker's drops and the defender's boosts.
+    let attack_boost = match attacker.boost(attack_stat) {
+        boost if critical => boost.max(0),
+        boost => boost,
+    };
+    let defense_boost = match defender.boost(defense_stat) {
+        boost if critical => boost.min(0),
+        boost => boost,
+    };
+
+    let mut attack_modifiers: Vec<u32> = Vec::new();
+    if physical && (attacker.has_ability(Ability::HugePower) || attacker.has_ability(Ability::PurePower)) {
+        attack_modifiers.push(8192);
+    }
+    if physical && attacker.has_ability(Ability::Hustle) {
+        attack_modifiers.push(6144);
+    }
+    if (physical && attacker.has_item(Item::ChoiceBand)) || (!physical && attacker.has_item(Item::ChoiceSpecs)) {
+        attack_modifiers.push(6144);
+    }
+    if defender.has_ability(Ability::ThickFat) && (attack.type_name == "Fire" || attack.type_name == "Ice") {
+        attack_modifiers.push(2048);
+    }
+    let attack_value = apply_modifier(
+        apply_boost(attacker.stat(attack_stat), attack_boost),
+        chain_modifiers(&attack_modifiers),
+    )
+    .max(1);
+
+    let mut defense_modifiers: Vec<u32> = Vec::new();
+    if !physical && defender.has_item(Item::AssaultVest) {
+        defense_modifiers.push(6144);
+    }
+    if defender.has_item(Item::Eviolite) {
+        defense_modifiers.push(6144);
+    }
+    let defense_value = apply_modifier(
+        apply_boost(defender.stat(defense_stat), defense_boost),
+        chain_modifiers(&defense_modifiers),
+    )
+    .max(1);
+
+    let level = u32::from(attacker.level);
+    let mut base_damage = (2 * level / 5 + 2) * power * attack_value / defense_value / 50 + 2;
+    if critical {
+        base_damage = base_damage * 3 / 2;
+    }
+
+    let stab = attacker.types.contains(&attack.type_name);
+    let stab_modifier = match stab {
+        true if attacker.has_ability(Ability::Adaptability) => 8192,
+        true => 6144,
+        false => NEUTRAL_MODIFIER,
+    };
+
+    let mut final_modifiers: Vec<u32> = Vec::new();
+    // Assumes the defender is at full HP.
+    if defender.has_ability(Ability::Multiscale) {
+        final_modifiers.push(2048);
+    }
+    if effectiveness > 1.0
+        && (defender.has_ability(Ability::Filter)
+            || defender.has_ability(Ability::SolidRock)
+            || defender.has_ability(Ability::PrismArmor))
+    {
+        final_modifiers.push(3072);
+    }
+    if effectiveness < 1.0 && attacker.has_ability(Ability::TintedLens) {
+        final_modifiers.push(8192);
+    }
+    if effectiveness > 1.0 && attacker.has_item(Item::ExpertBelt) {
+        final_modifiers.push(4915);
+    }
+    if attacker.has_item(Item::LifeOrb) {
+        final_modifiers.push(5324);
+    }
+    let final_modifier = chain_modifiers(&final_modifiers);
+
+    let mut rolls = [0u16; 16];
+    for (i, roll) in rolls.iter_mut().enumerate() {
+        let mut damage = base_damage * (85 + i as u32) / 100;
+        damage = apply_modifier(damage, stab_modifier);
+        damage = (f64::from(damage) * f64::from(effectiveness)).floor() as u32;
+        damage = apply_modifier(damage.max(1), final_modifier);
+        *roll = damage.max(1).min(u32::from(u16::MAX)) as u16;
+    }
+    DamageResult { rolls, defender_hp }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combatant(types: &[&str], stats: [u16; 6]) -> Combatant {
+        Combatant {
+            name: "Test".to_owned(),
+            level: 100,
+            types: types.iter().map(|type_name| type_name.to_string()).collect(),
+            stats,
+            boosts: [0; 6],
+            ability: None,
+            item: None,
+        }
+    }
+
+    fn physical(type_name: &str, power: u16) -> Move {
+        Move {
+            name: "Test".to_owned(),
+            type_name: type_name.to_owned(),
+            split: Split::Physical,
+            power,
+        }
+    }
+
+    // Bulbapedia's worked example: a level 75 Glaceon with 123 Attack using Ice Fang
+    // on a Garchomp with 163 Defense does 168-196 damage.
+    #[test]
+    fn glaceon_ice_fang_vs_garchomp() {
+        let mut glaceon = combatant(&["Ice"], [0, 123, 0, 0, 0, 0]);
+        glaceon.level = 75;
+        let garchomp = combatant(&["Dragon", "Ground"], [300, 0, 163, 0, 0, 0]);
+        let result = calculate(&glaceon, &garchomp, &physical("Ice", 65), 4.0, false);
+        assert_eq!(result.min(), 168);
+        assert_eq!(result.max(), 196);
+    }
+
+    // 252+ Atk Garchomp Earthquake vs. 252 HP / 0 Def Heatran: 684-808 (177.2 - 209.3%), guaranteed OHKO.
+    #[test]
+    fn garchomp_earthquake_vs_heatran() {
+        use crate::dex::stats::{calc_hp, calc_stat};
+        let garchomp = combatant(&["Dragon", "Ground"], [0, calc_stat(130, 31, 252, 100, 110), 0, 0, 0, 0]);
+        let heatran = combatant(
+            &["Fire", "Steel"],
+            [calc_hp(91, 31, 252, 100), 0, calc_stat(106, 31, 0, 100, 100), 0, 0, 0],
+        );
+        assert_eq!(garchomp.stats[1], 394);
+        assert_eq!(heatran.stats[0], 386);
+        assert_eq!(heatran.stats[2], 248);
+        let result = calculate(&garchomp, &heatran, &physical("Ground", 100), 4.0, false);
+        assert_eq!(
+            result.rolls,
+            [684, 696, 700, 708, 720, 724, 732, 744, 748, 756, 768, 772, 780, 792, 796, 808]
+        );
+        assert_eq!(result.ko_chance(), Some((1, 1.0)));
+        assert_eq!(format!("{:.1}", result.percent(result.min())), "177.2");
+        assert_eq!(format!("{:.1}", result.percent(result.max())), "209.3");
+    }
+
+    #[test]
+    fn choice_band_and_crit() {
+        let mut attacker = combatant(&["Normal"], [0, 200, 0, 0, 0, 0]);
+        attacker.item = Some(Item::ChoiceBand);
+        let defender = combatant(&["Normal"], [300, 0, 200, 0, 0, 0]);
+        let banded = calculate(&attacker, &defender, &physical("Normal", 80), 1.0, false);
+        // floor(floor(42 * 80 * 300 / 200) / 50) + 2 = 102, then STAB.
+        assert_eq!(banded.max(), 153);
+        let crit = calculate(&attacker, &defender, &physical("Normal", 80), 1.0, true);
+        // 102 * 1.5 = 153, then STAB: 229.5 rounds down.
+        assert_eq!(crit.max(), 229);
+    }
+
+    #[test]
+    fn immunities_deal_no_damage() {
+        let attacker = combatant(&["Ground"], [0, 300, 0, 0, 0, 0]);
+        let mut defender = combatant(&["Electric"], [300, 0, 200, 0, 0, 0]);
+        let result = calculate(&attacker, &defender, &physical("Ground", 100), 0.0, false);
+        assert_eq!(result.max(), 0);
+        assert!(result.ko_chance().is_none());
+
+        defender.ability = Some(Ability::Levitate);
+        let result = calculate(&attacker, &defender, &physical("Ground", 100), 2.0, false);
+        assert_eq!(result.max(), 0);
+    }
+
+    #[test]
+    fn boosts_and_crits() {
+        let mut attacker = combatant(&["Fire"], [0, 200, 0, 0, 0, 0]);
+        let mut defender = combatant(&["Water"], [300, 0, 200, 0, 0, 0]);
+        attacker.boosts[Stat::Atk.index().unwrap()] = 2;
+        let boosted = calculate(&attacker, &defender, &physical("Normal", 100), 1.0, false);
+        // Attack doubles at +2: floor(floor(42 * 100 * 400 / 200) / 50) + 2.
+        assert_eq!(boosted.max(), 170);
+
+        attacker.boosts[Stat::Atk.index().unwrap()] = -1;
+        defender.boosts[Stat::Def.index().unwrap()] = 1;
+        let crit = calculate(&attacker, &defender, &physical("Normal", 100), 1.0, true);
+        let neutral_crit = {
+            attacker.boosts = [0; 6];
+            defender.boosts = [0; 6];
+            calculate(&attacker, &defender, &physical("Normal", 100), 1.0, true)
+        };
+        assert_eq!(crit.rolls, neutral_crit.rolls);
+    }
+
+    #[test]
+    fn ko_chance_over_multiple_hits() {
+        let result = DamageResult {
+            rolls: [40, 40, 40, 40, 40, 40, 40, 40, 50, 50, 50, 50, 50, 50, 50, 50],
+            defender_hp: 100,
+        };
+        // Two hits only KO if both roll 50.
+        assert_eq!(result.ko_chance(), Some((2, 0.25)));
+        assert!((result.percent(50) - 50.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn item_and_ability_names() {
+        assert_eq!(Item::from_name("choice band"), Some(Item::ChoiceBand));
+        assert_eq!(Ability::from_name("Thick Fat"), Some(Ability::ThickFat));
+        assert_eq!(Ability::from_name("Blaze"), None);
+    }
+}
@@ -0,0 +1,14 @@
+select 
+    m.id,
+    m.name,
+    t.name as type_name,
+    s.name as split_name,
+    m.power
+from 
+    moves m
+left join
+    types t on (t.id = m.type)
+left join
+    splits s on (s.id = m.split)
+where
+	m.id = ?1;
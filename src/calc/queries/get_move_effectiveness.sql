@@ -0,0 +1,12 @@
+select
+    (case when (p.type1 = p.type2) then bt.effectiveness else (bt.effectiveness * bt2.effectiveness) end) as [effectiveness]
+from 
+    pokemon p
+inner join
+    types t on (t.name = ?2)
+inner join
+    base_types bt on ((bt.base_type = t.id) and (bt.type = p.type1))
+inner join
+    base_types bt2 on ((bt2.base_type = t.id) and (bt2.type = p.type2))
+where
+    p.id = ?1;
@@ -0,0 +1,218 @@
+//! PNG stat cards, shown instead of the dex embed's fields in guilds that turned them on with `/config style`.
+//! Embeds cap field lengths and look cramped on mobile, a picture looks the same everywhere.
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+use std::sync::LazyLock;
+use crate::datasets::Dataset;
+use crate::dex::{self, Ability, Pokemon};
+use crate::guild_config::Labels;
+use crate::sprites::{self, Variant};
+
+/// Name of the card attachment, which the dex embed shows with `attachment://`.
+pub const FILE_NAME: &str = "card.png";
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 360;
+const MARGIN: f32 = 28.0;
+const SPRITE_SIZE: u32 = 192;
+const BACKGROUND: Rgba<u8> = Rgba([43, 45, 49, 255]);
+const TRACK: Rgba<u8> = Rgba([30, 31, 34, 255]);
+const TEXT: Rgba<u8> = Rgba([242, 243, 245, 255]);
+const MUTED: Rgba<u8> = Rgba([181, 186, 193, 255]);
+
+static REGULAR: LazyLock<FontRef<'static>> =
+    LazyLock::new(|| FontRef::try_from_slice(include_bytes!("../assets/fonts/DejaVuSans.ttf")).unwrap());
+static BOLD: LazyLock<FontRef<'static>> =
+    LazyLock::new(|| FontRef::try_from_slice(include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf")).unwrap());
+
+/// The card of a Pokemon showing a variant of its sprite, or None if it could not be rendered
+/// and the embed should be used instead.
+pub async fn for_pokemon(
+    dataset: &Dataset,
+    pokemon: &Pokemon,
+    abilities: &[Ability],
+    labels: &Labels,
+    variant: Variant,
+) -> Option<Vec<u8>> {
+    let sprite = sprites::find(dataset, pokemon.sprite.as_deref(), variant).png().await;
+    match render(pokemon, abilities, &sprite, labels) {
+        Ok(png) => Some(png),
+        Err(e) => {
+            tracing::warn!(pokemon_id = pokemon.id, error = %e, "Failed to render stat card");
+            None
+        }
+    }
+}
+
+/// Draws the card: name, types, stat bars, abilities and sprite.
+pub fn render(pokemon: &Pokemon, abilities: &[Ability], sprite: &[u8], labels: &Labels) -> Result<Vec<u8>, image::ImageError> {
+    let mut card = RgbaImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+    let accent = type_color(pokemon.type1_name.as_deref());
+    fill(&mut card, 0, 0, 8, HEIGHT, accent);
+
+    let title = format!("#{} {}", pokemon.pokedex_id.unwrap_or(0), pokemon.name);
+    draw_text(&mut card, &BOLD, 30.0, MARGIN, 18.0, TEXT, &title);
+
+    // Type badges.
+    let mut x = MARGIN;
+    let mut types = vec![pokemon.type1_name.as_deref()];
+    if pokemon.type2.is_some() && pokemon.type2 != pokemon.type1 {
+        types.push(pokemon.type2_name.as_deref());
+    }
+    for name in types.into_iter().flatten() {
+        let width = text_width(&BOLD, 15.0, name) + 20.0;
+        fill(&mut card, x as u32, 62, width as u32, 26, type_color(Some(name)));
+        draw_text(&mut card, &BOLD, 15.0, x + 10.0, 66.0, TEXT, name);
+        x += width + 8.0;
+    }
+
+    // Stat bars, scaled to the highest possible base stat.
+    let names = ["HP", "Atk", "Def", "SpA", "SpD", "Spe"];
+    let bar_x = MARGIN + 96.0;
+    let bar_width = 260.0;
+    for (i, (name, value)) in names.iter().zip(pokemon.base_stats()).enumerate() {
+        let y = 106.0 + i as f32 * 28.0;
+        draw_text(&mut card, &REGULAR, 17.0, MARGIN, y, MUTED, name);
+        let value_text = value.to_string();
+        draw_text(&mut card, &BOLD, 17.0, bar_x - 12.0 - text_width(&BOLD, 17.0, &value_text), y, TEXT, &value_text);
+        fill(&mut card, bar_x as u32, y as u32 + 3, bar_width as u32, 14, TRACK);
+        let filled = (bar_width * value as f32 / 255.0).max(2.0);
+        fill(&mut card, bar_x as u32, y as u32 + 3, filled as u32, 14, stat_color(value));
+    }
+    let y = 106.0 + 6.0 * 28.0;
+    draw_text(&mut card, &REGULAR, 17.0, MARGIN, y, MUTED, "BST");
+    let total = pokemon.base_total.to_string();
+    draw_text(&mut card, &BOLD, 17.0, bar_x - 12.0 - text_width(&BOLD, 17.0, &total), y, TEXT, &total);
+
+    // Abilities, cut short if they do not fit.
+    let y = y + 40.0;
+    draw_text(&mut card, &BOLD, 16.0, MARGIN, y, TEXT, labels.abilities);
+    let names = if abilities.is_empty() {
+        labels.none.to_owned()
+    } else {
+        abilities.iter().map(|ability| ability.name.as_str()).collect::<Vec<&str>>().join(", ")
+    };
+    let x = MARGIN + text_width(&BOLD, 16.0, labels.abilities) + 12.0;
+    draw_text(&mut card, &REGULAR, 16.0, x, y, MUTED, &fit(&REGULAR, 16.0, &names, WIDTH as f32 - MARGIN - x));
+
+    let sprite = prepare_sprite(sprite)?;
+    let x = WIDTH - 28 - SPRITE_SIZE + SPRITE_SIZE.saturating_sub(sprite.width()) / 2;
+    let y = 70 + SPRITE_SIZE.saturating_sub(sprite.height()) / 2;
+    imageops::overlay(&mut card, &sprite, x as i64, y as i64);
+
+    let mut png = Vec::new();
+    card.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Decodes a sprite and scales it to fit `SPRITE_SIZE`, keeping the pixels of small sprites sharp.
+pub fn prepare_sprite(png: &[u8]) -> Result<RgbaImage, image::ImageError> {
+    let mut sprite = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+    // Animated sprites stack their frames vertically, only the first one is shown.
+    if sprite.height() >= 2 * sprite.width() {
+        sprite = imageops::crop_imm(&sprite, 0, 0, sprite.width(), sprite.width()).to_image();
+    }
+    // Downloaded decomp sprites have no transparency, their background is the palette's first color, as in the corner.
+    if sprite.pixels().all(|pixel| pixel[3] == 255) {
+        let background = *sprite.get_pixel(0, 0);
+        for pixel in sprite.pixels_mut() {
+            if *pixel == background {
+                pixel[3] = 0;
+            }
+        }
+    }
+    let largest = sprite.width().max(sprite.height()).max(1);
+    if largest > SPRITE_SIZE {
+        // Larger artwork is not pixel art, so it is shrunk smoothly.
+        let width = (sprite.width() * SPRITE_SIZE / largest).max(1);
+        let height = (sprite.height() * SPRITE_SIZE / largest).max(1);
+        return Ok(imageops::resize(&sprite, width, height, FilterType::Triangle));
+    }
+    let scale = SPRITE_SIZE / largest;
+    Ok(imageops::resize(&sprite, sprite.width() * scale, sprite.height() * scale, FilterType::Nearest))
+}
+
+fn type_color(type_name: Option<&str>) -> Rgba<u8> {
+    let color = dex::get_color_from_type(type_name.unwrap_or(""));
+    Rgba([color.r(), color.g(), color.b(), 255])
+}
+
+/// Red for low stats through green to cyan for the highest ones.
+fn stat_color(value: u8) -> Rgba<u8> {
+    match value {
+        0..=59 => Rgba([240, 85, 85, 255]),
+        60..=89 => Rgba([247, 151, 64, 255]),
+        90..=119 => Rgba([242, 208, 76, 255]),
+        120..=149 => Rgba([120, 205, 90, 255]),
+        _ => Rgba([80, 200, 220, 255]),
+    }
+}
+
+fn fill(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// Draws a line of text with its top left corner at (x, y).
+fn draw_text(image: &mut RgbaImage, font: &FontRef, size: f32, x: f32, y: f32, color: Rgba<u8>, text: &str) {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut caret = x;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += font.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(font.scale(), point(caret, y + font.ascent()));
+        caret += font.h_advance(id);
+        previous = Some(id);
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let (px, py) = (bounds.min.x as i32 + gx as i32, bounds.min.y as i32 + gy as i32);
+            if px < 0 || py < 0 || px >= image.width() as i32 || py >= image.height() as i32 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            for channel in 0..3 {
+                pixel[channel] =
+                    (pixel[channel] as f32 * (1.0 - coverage) + color[channel] as f32 * coverage).round() as u8;
+            }
+        });
+    }
+}
+
+fn text_width(font: &FontRef, size: f32, text: &str) -> f32 {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// The text, cut short with an ellipsis if it is wider than `max_width`.
+fn fit(font: &FontRef, size: f32, text: &str, max_width: f32) -> String {
+    if text_width(font, size, text) <= max_width {
+        return text.to_owned();
+    }
+    let mut fitted: String = text.to_owned();
+    while !fitted.is_empty() && text_width(font, size, &format!("{}…", fitted)) > max_width {
+        fitted.pop();
+    }
+    format!("{}…", fitted.trim_end())
+}
@@ -0,0 +1,143 @@
+use poise::serenity_prelude as serenity;
+use std::collections::BTreeSet;
+use crate::{Context, Error};
+use crate::datasets::{self, Dataset};
+use crate::dex::{self, autocomplete, reply_error, truncate_list, Pokemon, FIELD_LIMIT};
+use crate::dex::eggmoves::get_egg_sets;
+use crate::dex::hmtm::get_hmtm_sets;
+use crate::dex::levelup::get_levelup_sets;
+use crate::dex::stats::Stat;
+use crate::dex::tutor::get_tutor_sets;
+use crate::dex::type_effectiveness::get_effectiveness;
+
+/// Compare two Pokemon side by side.
+///
+/// Shows stat differences, types, abilities, defensive matchups and the moves only one of them learns.
+///
+/// Usage:
+/// /compare Blaziken Infernape
+#[poise::command(prefix_command, slash_command)]
+pub async fn compare(
+    ctx: Context<'_>,
+    #[description = "First Pokemon."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    a: String,
+    #[description = "Second Pokemon."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    b: String,
+    #[description = "Game dataset to use."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let Some(a) = dex::resolve_pokemon(&dataset, &a)? else {
+        return reply_error(ctx, dex::unknown_pokemon(&a)).await;
+    };
+    let Some(b) = dex::resolve_pokemon(&dataset, &b)? else {
+        return reply_error(ctx, dex::unknown_pokemon(&b)).await;
+    };
+
+    let (a_moves, b_moves) = (get_learnset(&dataset, &a.id)?, get_learnset(&dataset, &b.id)?);
+    let only_a: Vec<&str> = a_moves.difference(&b_moves).map(|m| m.as_str()).collect();
+    let only_b: Vec<&str> = b_moves.difference(&a_moves).map(|m| m.as_str()).collect();
+
+    let embed = serenity::CreateEmbed::new()
+        .title(format!("{0} vs. {1}", a.name, b.name))
+        .colour(dex::get_color_from_type(a.type1_name.as_deref().unwrap_or("")))
+        .field(format!("{} Types", a.name), types(&a), true)
+        .field(format!("{} Types", b.name), types(&b), true)
+        .field("\u{200b}", "\u{200b}", true)
+        .field(format!("{} Abilities", a.name), abilities(&dataset, &a.id), true)
+        .field(format!("{} Abilities", b.name), abilities(&dataset, &b.id), true)
+        .field("\u{200b}", "\u{200b}", true)
+        .field("Stats", stats_table(&a, &b), false)
+        .field("Defensive Matchups", matchups_table(&dataset, &a, &b)?, false)
+        .field(
+            format!("Only {0} learns ({1})", a.name, only_a.len()),
+            truncate_list(&only_a, FIELD_LIMIT),
+            false,
+        )
+        .field(
+            format!("Only {0} learns ({1})", b.name, only_b.len()),
+            truncate_list(&only_b, FIELD_LIMIT),
+            false,
+        );
+    ctx.send(poise::CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
+
+fn types(p: &Pokemon) -> String {
+    match (&p.type1_name, &p.type2_name) {
+        (Some(type1), Some(type2)) if type1 != type2 => format!("{}, {}", type1, type2),
+        (Some(type1), _) => type1.clone(),
+        _ => "None".to_owned(),
+    }
+}
+
+fn abilities(dataset: &Dataset, pokemon_id: &u16) -> String {
+    let names = dex::get_abilities(dataset, pokemon_id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|ability| ability.name)
+        .collect::<Vec<String>>();
+    if names.is_empty() {
+        "None".to_owned()
+    } else {
+        names.join("\n")
+    }
+}
+
+fn stats_table(a: &Pokemon, b: &Pokemon) -> String {
+    let (a_stats, b_stats) = (a.base_stats(), b.base_stats());
+    let mut rows: Vec<(&str, i32, i32)> = Stat::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, stat)| (stat.short_name(), i32::from(a_stats[i]), i32::from(b_stats[i])))
+        .collect();
+    rows.push(("BST", i32::from(a.base_total), i32::from(b.base_total)));
+    let mut table = "```c\n     A    B    Diff\n".to_owned();
+    for (name, a_stat, b_stat) in rows {
+        table.push_str(&format!(
+            "{0:<4} {1:<4} {2:<4} {3:+}\n",
+            name.to_owned() + ":",
+            a_stat,
+            b_stat,
+            a_stat - b_stat
+        ));
+    }
+    table.push_str("```");
+    table
+}
+
+/// Attacking types that either Pokemon is not neutral to.
+fn matchups_table(dataset: &Dataset, a: &Pokemon, b: &Pokemon) -> Result<String, Error> {
+    let (a_matchups, b_matchups) = (get_effectiveness(dataset, &a.id)?, get_effectiveness(dataset, &b.id)?);
+    let mut table = "```c\n          A     B\n".to_owned();
+    for (a_matchup, b_matchup) in a_matchups.iter().zip(b_matchups.iter()) {
+        if a_matchup.defensive == 1.0 && b_matchup.defensive == 1.0 {
+            continue;
+        }
+        table.push_str(&format!(
+            "{0:<9} {1:<5} {2}\n",
+            a_matchup.attacking_type.clone() + ":",
+            a_matchup.defensive,
+            b_matchup.defensive
+        ));
+    }
+    table.push_str("```");
+    Ok(table)
+}
+
+/// Every move a Pokemon learns, whether by level-up, HM/TM, tutor or breeding.
+fn get_learnset(dataset: &Dataset, pokemon_id: &u16) -> Result<BTreeSet<String>, rusqlite::Error> {
+    let mut moves: BTreeSet<String> = BTreeSet::new();
+    moves.extend(get_levelup_sets(dataset, pokemon_id)?.into_iter().map(|m| m.move_name));
+    moves.extend(get_hmtm_sets(dataset, pokemon_id)?.into_iter().map(|m| m.move_name));
+    moves.extend(get_tutor_sets(dataset, pokemon_id)?.into_iter().map(|m| m.move_name));
+    moves.extend(get_egg_sets(dataset, pokemon_id)?.into_iter().map(|m| m.move_name));
+    Ok(moves)
+}
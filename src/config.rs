@@ -0,0 +1,341 @@
+//! The bot's configuration, read once at startup.
+//!
+//! Every key is read, from lowest to highest precedence, from rowedex.toml (or the file given with `--config`
+//! or `ROWEDEX_CONFIG`), then from its env var, then from its command line flag, see `KEYS`.
+//! See rowedex.example.toml for every key and its default.
+
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing_subscriber::EnvFilter;
+use crate::Error;
+
+const CONFIG_PATH: &str = "rowedex.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Every key that can be overridden, with its env var and command line flag.
+const KEYS: &[(&str, &str, &str)] = &[
+    ("token", "DISCORD_TOKEN", "--token"),
+    ("token_file", "DISCORD_TOKEN_FILE", "--token-file"),
+    ("prefix", "BOT_PREFIX", "--prefix"),
+    ("additional_prefixes", "BOT_ADDITIONAL_PREFIXES", "--additional-prefixes"),
+    ("owners", "BOT_OWNERS", "--owners"),
+    ("dev_guilds", "DEV_GUILDS", "--dev-guilds"),
+    ("database.rowedex", "ROWEDEX_DB", "--db"),
+    ("database.datasets", "DATASETS_FILE", "--datasets"),
+    ("database.settings", "SETTINGS_DB", "--settings-db"),
+    ("database.analytics", "ANALYTICS_DB", "--analytics-db"),
+    ("logging.level", "LOG_LEVEL", "--log-level"),
+    ("logging.format", "LOG_FORMAT", "--log-format"),
+    ("http.addr", "HTTP_ADDR", "--http-addr"),
+    ("sprites.dir", "SPRITES_DIR", "--sprites-dir"),
+    ("sprites.cache", "SPRITE_CACHE_DIR", "--sprite-cache-dir"),
+    ("pokemon_of_the_day.time", "POTD_TIME", "--potd-time"),
+    ("features.analytics", "FEATURE_ANALYTICS", "--analytics"),
+    ("features.hot_reload", "FEATURE_HOT_RELOAD", "--hot-reload"),
+    ("features.edit_tracking", "FEATURE_EDIT_TRACKING", "--edit-tracking"),
+];
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Discord bot token. Prefer `token_file`, so it does not end up in the config file or the environment.
+    pub token: Option<String>,
+    /// File holding the Discord bot token, read when `token` is not set.
+    pub token_file: Option<PathBuf>,
+    /// Prefix of prefix commands in servers that did not pick their own.
+    pub prefix: String,
+    /// Prefixes that work everywhere, whatever the server's prefix.
+    pub additional_prefixes: Vec<String>,
+    /// Users allowed to run owner commands, on top of the application's owner.
+    pub owners: Vec<u64>,
+    /// Servers to register the commands in instead of globally, so changes show up right away while testing.
+    pub dev_guilds: Vec<u64>,
+    pub database: Database,
+    pub logging: Logging,
+    pub http: Http,
+    pub sprites: Sprites,
+    pub pokemon_of_the_day: PokemonOfTheDay,
+    pub features: Features,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Database {
+    /// R.O.W.E's database, served when there is no datasets file.
+    pub rowedex: PathBuf,
+    pub datasets: PathBuf,
+    pub settings: PathBuf,
+    pub analytics: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Logging {
+    /// `tracing_subscriber::EnvFilter` directives.
+    pub level: String,
+    pub format: LogFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Http {
+    /// Address to serve `/metrics` and `/health` on, if any.
+    pub addr: Option<SocketAddr>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sprites {
+    /// Sprites imported with `dexctl sprites`, served as attachments.
+    pub dir: PathBuf,
+    /// Sprites downloaded from the datasets' `sprite_base`, for those that were not imported.
+    pub cache: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PokemonOfTheDay {
+    /// Time the Pokemon of the day is posted at, as HH:MM in UTC, in servers that picked a channel for it.
+    pub time: String,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Features {
+    /// Record command and button use in the analytics database.
+    pub analytics: bool,
+    /// Pick up replaced dataset database files without `/admin reload`.
+    pub hot_reload: bool,
+    /// Rerun prefix commands when their message is edited.
+    pub edit_tracking: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            token: None,
+            token_file: None,
+            prefix: "~".to_owned(),
+            additional_prefixes: vec!["hey bot".to_owned(), "hey bot,".to_owned()],
+            owners: Vec::new(),
+            dev_guilds: Vec::new(),
+            database: Database::default(),
+            logging: Logging::default(),
+            http: Http::default(),
+            sprites: Sprites::default(),
+            pokemon_of_the_day: PokemonOfTheDay::default(),
+            features: Features::default(),
+        }
+    }
+}
+
+impl Default for Database {
+    fn default() -> Database {
+        Database {
+            rowedex: PathBuf::from("rowedex.db"),
+            datasets: PathBuf::from("datasets.toml"),
+            settings: PathBuf::from("settings.db"),
+            analytics: PathBuf::from("analytics.db"),
+        }
+    }
+}
+
+impl Default for Logging {
+    fn default() -> Logging {
+        Logging {
+            level: "warn,hammer_plus=info".to_owned(),
+            format: LogFormat::Text,
+        }
+    }
+}
+
+impl Default for Sprites {
+    fn default() -> Sprites {
+        Sprites {
+            dir: PathBuf::from("sprites"),
+            cache: PathBuf::from("sprite_cache"),
+        }
+    }
+}
+
+impl Default for PokemonOfTheDay {
+    fn default() -> PokemonOfTheDay {
+        PokemonOfTheDay { time: "12:00".to_owned() }
+    }
+}
+
+impl Default for Features {
+    fn default() -> Features {
+        Features {
+            analytics: true,
+            hot_reload: true,
+            edit_tracking: true,
+        }
+    }
+}
+
+/// The configuration loaded at startup.
+pub fn get() -> &'static Config {
+    CONFIG.get().expect("the configuration is loaded at startup")
+}
+
+/// Loads the configuration from the config file, env vars and `args`, and makes it available through `get`.
+pub fn init(args: &[String]) -> Result<&'static Config, Error> {
+    let config = Config::load(args)?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+impl Config {
+    fn load(args: &[String]) -> Result<Config, Error> {
+        let mut flags: Vec<(&str, &str)> = Vec::new();
+        let mut path = std::env::var("ROWEDEX_CONFIG").ok().map(PathBuf::from);
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let Some(value) = args.next() else {
+                return Err(format!("{} needs a value.", flag).into());
+            };
+            if flag == "--config" {
+                path = Some(PathBuf::from(value));
+                continue;
+            }
+            match KEYS.iter().find(|(_, _, name)| name == flag) {
+                Some((key, _, _)) => flags.push((key, value)),
+                None => return Err(format!("Unknown option {}.", flag).into()),
+            }
+        }
+
+        // The default file is optional, one that was asked for is not.
+        let mut config = match path {
+            Some(path) => Config::read(&path)?,
+            None if std::path::Path::new(CONFIG_PATH).exists() => Config::read(CONFIG_PATH.as_ref())?,
+            None => Config::default(),
+        };
+        for (key, var, _) in KEYS {
+            if let Ok(value) = std::env::var(var) {
+                config.set(key, &value).map_err(|e| format!("{} ({}): {}", key, var, e))?;
+            }
+        }
+        for (key, value) in flags {
+            let (_, _, flag) = KEYS.iter().find(|(name, _, _)| *name == key).unwrap();
+            config.set(key, value).map_err(|e| format!("{} ({}): {}", key, flag, e))?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(path: &std::path::Path) -> Result<Config, Error> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?)
+    }
+
+    /// Overrides a key with a value from an env var or flag.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "token" => self.token = Some(value.to_owned()),
+            "token_file" => self.token_file = Some(PathBuf::from(value)),
+            "prefix" => self.prefix = value.to_owned(),
+            "additional_prefixes" => self.additional_prefixes = list(value).map(str::to_owned).collect(),
+            "owners" => self.owners = list(value).map(id).collect::<Result<_, _>>()?,
+            "dev_guilds" => self.dev_guilds = list(value).map(id).collect::<Result<_, _>>()?,
+            "database.rowedex" => self.database.rowedex = PathBuf::from(value),
+            "database.datasets" => self.database.datasets = PathBuf::from(value),
+            "database.settings" => self.database.settings = PathBuf::from(value),
+            "database.analytics" => self.database.analytics = PathBuf::from(value),
+            "logging.level" => self.logging.level = value.to_owned(),
+            "logging.format" => {
+                self.logging.format = match value {
+                    "text" => LogFormat::Text,
+                    "json" => LogFormat::Json,
+                    _ => return Err(format!("expected text or json, got {}", value)),
+                }
+            }
+            "http.addr" => self.http.addr = Some(value.parse().map_err(|e| format!("{}: {}", value, e))?),
+            "sprites.dir" => self.sprites.dir = PathBuf::from(value),
+            "sprites.cache" => self.sprites.cache = PathBuf::from(value),
+            "pokemon_of_the_day.time" => self.pokemon_of_the_day.time = value.to_owned(),
+            "features.analytics" => self.features.analytics = flag(value)?,
+            "features.hot_reload" => self.features.hot_reload = flag(value)?,
+            "features.edit_tracking" => self.features.edit_tracking = flag(value)?,
+            _ => unreachable!("{} is in KEYS", key),
+        }
+        Ok(())
+    }
+
+    /// Checks what deserializing cannot, naming the key at fault.
+    fn validate(&self) -> Result<(), Error> {
+        if self.prefix.trim().is_empty() {
+            return Err("prefix: must not be empty.".into());
+        }
+        if self.additional_prefixes.iter().any(|prefix| prefix.trim().is_empty()) {
+            return Err("additional_prefixes: must not contain an empty prefix.".into());
+        }
+        for (key, ids) in [("owners", &self.owners), ("dev_guilds", &self.dev_guilds)] {
+            if ids.contains(&0) {
+                return Err(format!("{}: 0 is not an id.", key).into());
+            }
+        }
+        if let Err(e) = EnvFilter::try_new(&self.logging.level) {
+            return Err(format!("logging.level: {}", e).into());
+        }
+        if self.pokemon_of_the_day.seconds().is_none() {
+            return Err(format!("pokemon_of_the_day.time: expected HH:MM, got {}.", self.pokemon_of_the_day.time).into());
+        }
+        Ok(())
+    }
+
+    /// The Discord token, read from `token_file` unless set directly. Only the bot needs one, not dexctl.
+    pub fn token(&self) -> Result<String, Error> {
+        match (&self.token, &self.token_file) {
+            (Some(token), _) => Ok(token.trim().to_owned()),
+            (None, Some(path)) => match std::fs::read_to_string(path) {
+                Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_owned()),
+                Ok(_) => Err(format!("token_file: {} is empty.", path.display()).into()),
+                Err(e) => Err(format!("token_file: {}: {}", path.display(), e).into()),
+            },
+            (None, None) => {
+                Err("token: missing, set token or token_file (DISCORD_TOKEN or DISCORD_TOKEN_FILE).".into())
+            }
+        }
+    }
+}
+
+impl PokemonOfTheDay {
+    /// `time` in seconds after midnight, if it is a valid time.
+    pub fn seconds(&self) -> Option<u64> {
+        let (hours, minutes) = self.time.trim().split_once(':')?;
+        match (hours.parse::<u64>(), minutes.parse::<u64>()) {
+            (Ok(hours), Ok(minutes)) if hours < 24 && minutes < 60 => Some(hours * 3600 + minutes * 60),
+            _ => None,
+        }
+    }
+}
+
+/// Items of a comma separated list.
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|item| !item.is_empty())
+}
+
+fn id(value: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(id) if id != 0 => Ok(id),
+        _ => Err(format!("{} is not an id.", value)),
+    }
+}
+
+fn flag(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(format!("expected true or false, got {}", value)),
+    }
+}
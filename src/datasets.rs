@@ -0,0 +1,444 @@
+//! The game datasets the bot can look things up in.
+//!
+//! Datasets are registered in datasets.toml (`database.datasets` in the configuration).
+//! Without one, the bot serves R.O.W.E from rowedex.db (`database.rowedex`).
+//! Each guild or channel picks a default with `/dataset set`, and commands take a `dataset` option to override it.
+//!
+//! The bot never queries a dataset's database file directly. It serves a validated snapshot of it instead,
+//! so a new version of the file can be dropped in place and hot-reloaded (see `Datasets::reload`)
+//! while the old snapshot keeps being served if the new one fails the integrity checks.
+
+use poise::serenity_prelude as serenity;
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use crate::dexctl::check;
+use crate::{config, migrations, settings, Context, Error};
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+// How often database files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+pub struct Dataset {
+    /// Short name used by the `dataset` option and in button ids.
+    pub name: String,
+    /// Name shown to users.
+    pub title: String,
+    /// Database file the dataset is loaded from.
+    pub db: PathBuf,
+    /// Link on the dex embed's title, with `{species}` replaced by the Pokemon's internal name.
+    pub external_link: Option<String>,
+    /// Prefix for sprite paths in the database that are not full URLs.
+    pub sprite_base: String,
+    /// Snapshot of `db` that queries run against.
+    #[serde(skip)]
+    snapshot: PathBuf,
+    /// Modification time of `db` when the snapshot was taken.
+    #[serde(skip)]
+    db_modified: Option<SystemTime>,
+}
+
+impl Dataset {
+    fn rowe() -> Dataset {
+        Dataset {
+            name: "rowe".to_owned(),
+            title: "R.O.W.E".to_owned(),
+            db: config::get().database.rowedex.clone(),
+            external_link: Some(
+                "https://ydarissep.github.io/R.O.W.E-Pokedex/?species={species}&table=speciesTable".to_owned(),
+            ),
+            sprite_base: "https://raw.githubusercontent.com/BelialClover/RoweRepo/main/graphics/pokemon/".to_owned(),
+            snapshot: PathBuf::new(),
+            db_modified: None,
+        }
+    }
+
+    pub fn open(&self) -> rusqlite::Connection {
+        rusqlite::Connection::open(&self.snapshot).unwrap()
+    }
+
+    /// Snapshot of `db` that queries run against.
+    pub fn snapshot(&self) -> &Path {
+        &self.snapshot
+    }
+
+    pub fn external_link(&self, internal_name: &str) -> Option<String> {
+        self.external_link
+            .as_ref()
+            .map(|template| template.replace("{species}", internal_name))
+    }
+
+    /// Full URL of a sprite path from the database.
+    pub fn sprite_url(&self, sprite: &str) -> String {
+        if sprite.contains("://") {
+            sprite.to_owned()
+        } else {
+            format!("{}{}", self.sprite_base, sprite)
+        }
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.db).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// Copies `db` into a new, migrated snapshot. The snapshot file is removed again
+    /// once the returned dataset is dropped.
+    fn take_snapshot(&self) -> Result<Dataset, Error> {
+        std::fs::create_dir_all(SNAPSHOTS_DIR)?;
+        let millis = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_millis();
+        let snapshot = Path::new(SNAPSHOTS_DIR).join(format!("{}-{}.db", self.name, millis));
+        let db_modified = self.modified();
+        // VACUUM INTO reads the file in one transaction, so a file being written can't be half copied.
+        let source = rusqlite::Connection::open_with_flags(&self.db, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        source.execute("VACUUM INTO ?1", [snapshot.to_string_lossy()])?;
+        let dataset = Dataset {
+            name: self.name.clone(),
+            title: self.title.clone(),
+            db: self.db.clone(),
+            external_link: self.external_link.clone(),
+            sprite_base: self.sprite_base.clone(),
+            snapshot,
+            db_modified,
+        };
+        migrations::migrate(&mut dataset.open(), &migrations::DEX)?;
+        Ok(dataset)
+    }
+
+    /// Integrity check failures of the snapshot, as (check, subject) pairs.
+    fn failures(&self) -> Result<BTreeSet<(String, String)>, rusqlite::Error> {
+        Ok(check::check(&self.open())?
+            .checks
+            .into_iter()
+            .flat_map(|check| {
+                check
+                    .failures
+                    .into_iter()
+                    .map(move |failure| (check.name.to_owned(), failure.subject))
+            })
+            .collect())
+    }
+}
+
+impl Drop for Dataset {
+    fn drop(&mut self) {
+        if !self.snapshot.as_os_str().is_empty() {
+            let _ = std::fs::remove_file(&self.snapshot);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct DatasetsFile {
+    default: String,
+    #[serde(rename = "dataset")]
+    datasets: Vec<Dataset>,
+}
+
+pub struct Datasets {
+    /// Default from datasets.toml, used when no bot-wide default is set with `/admin default`.
+    configured_default: String,
+    default: RwLock<String>,
+    // Commands hold on to the Arc they resolved, so a reload never changes the data under a running command.
+    datasets: Vec<RwLock<Arc<Dataset>>>,
+}
+
+impl Datasets {
+    /// Reads datasets.toml, falling back to R.O.W.E alone if there is none, then migrates and snapshots every database.
+    pub fn load() -> Result<Datasets, Error> {
+        let path = &config::get().database.datasets;
+        let file = if path.exists() {
+            toml::from_str(&std::fs::read_to_string(path)?).map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            DatasetsFile {
+                default: "rowe".to_owned(),
+                datasets: vec![Dataset::rowe()],
+            }
+        };
+        if !file.datasets.iter().any(|dataset| dataset.name == file.default) {
+            return Err(format!("{}: the default dataset {} is not registered.", path.display(), file.default).into());
+        }
+        // Snapshots left behind by a previous run.
+        if let Ok(entries) = std::fs::read_dir(SNAPSHOTS_DIR) {
+            for entry in entries.flatten() {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+        let mut datasets: Vec<RwLock<Arc<Dataset>>> = Vec::new();
+        for dataset in &file.datasets {
+            if !dataset.db.exists() {
+                return Err(format!(
+                    "{}: the database of dataset {} ({}) does not exist.",
+                    path.display(),
+                    dataset.name,
+                    dataset.db.display()
+                )
+                .into());
+            }
+            // Hot-reloaded files are only migrated in their snapshot, until the next restart.
+            migrations::migrate(&mut rusqlite::Connection::open(&dataset.db)?, &migrations::DEX)
+                .map_err(|e| format!("Failed to migrate {}: {}", dataset.db.display(), e))?;
+            let snapshot = dataset
+                .take_snapshot()
+                .map_err(|e| format!("Could not load {}: {}", dataset.db.display(), e))?;
+            datasets.push(RwLock::new(Arc::new(snapshot)));
+        }
+        // A bot-wide default naming a dataset that was since unregistered is ignored.
+        let default = settings::get_bot_dataset()?
+            .filter(|name| file.datasets.iter().any(|dataset| &dataset.name == name))
+            .unwrap_or_else(|| file.default.clone());
+        Ok(Datasets {
+            configured_default: file.default,
+            default: RwLock::new(default),
+            datasets,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Dataset>> {
+        self.all()
+            .into_iter()
+            .find(|dataset| dataset.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn default(&self) -> Arc<Dataset> {
+        self.get(&self.default.read().unwrap()).expect("the default dataset is registered")
+    }
+
+    /// Changes the bot-wide default dataset, or goes back to the one in datasets.toml.
+    pub fn set_default(&self, name: Option<&str>) -> Result<Arc<Dataset>, Error> {
+        let dataset = match name {
+            Some(name) => self.get(name).ok_or_else(|| unknown(self, name))?,
+            None => self.get(&self.configured_default).expect("the default dataset is registered"),
+        };
+        settings::set_bot_dataset(name.map(|_| dataset.name.as_str()))?;
+        *self.default.write().unwrap() = dataset.name.clone();
+        Ok(dataset)
+    }
+
+    pub fn all(&self) -> Vec<Arc<Dataset>> {
+        self.datasets
+            .iter()
+            .map(|dataset| dataset.read().unwrap().clone())
+            .collect()
+    }
+
+    /// Snapshots a dataset's database file again and starts serving it if it passes validation.
+    ///
+    /// Validation fails if the new database fails any integrity check the one being served passes,
+    /// in which case the current snapshot keeps being served.
+    pub fn reload(&self, name: &str) -> Result<String, Error> {
+        let slot = self
+            .datasets
+            .iter()
+            .find(|dataset| dataset.read().unwrap().name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("There is no dataset called \"{}\".", name))?;
+        let current = slot.read().unwrap().clone();
+        let reloaded = current.take_snapshot()?;
+        let known = current.failures()?;
+        let failures = reloaded.failures()?;
+        let new_failures: Vec<String> = failures
+            .difference(&known)
+            .map(|(check, subject)| format!("{}: {}", check, subject))
+            .collect();
+        if !new_failures.is_empty() {
+            return Err(format!(
+                "{} failed {} integrity check(s) the current database passes, kept serving the current one: {}",
+                current.db.display(),
+                new_failures.len(),
+                new_failures.join("; ")
+            )
+            .into());
+        }
+        let summary = format!(
+            "Reloaded {0} from {1} ({2} known integrity check failures).",
+            reloaded.title,
+            reloaded.db.display(),
+            failures.len()
+        );
+        *slot.write().unwrap() = Arc::new(reloaded);
+        Ok(summary)
+    }
+}
+
+/// Reloads datasets whenever their database file changes. Runs until the bot stops.
+pub async fn watch(datasets: Arc<Datasets>) {
+    // Files that failed validation, so they are not retried until they change again.
+    let mut rejected: HashMap<String, SystemTime> = HashMap::new();
+    loop {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        for dataset in datasets.all() {
+            let Some(modified) = dataset.modified() else {
+                continue;
+            };
+            // Wait for the file to settle, it may still be being copied.
+            let settled = modified.elapsed().is_ok_and(|elapsed| elapsed >= Duration::from_secs(5));
+            if Some(modified) == dataset.db_modified
+                || rejected.get(&dataset.name) == Some(&modified)
+                || !settled
+            {
+                continue;
+            }
+            let (name, watched) = (dataset.name.clone(), datasets.clone());
+            match tokio::task::spawn_blocking(move || watched.reload(&name)).await {
+                Ok(Ok(summary)) => tracing::info!(dataset = %dataset.name, "{}", summary),
+                Ok(Err(e)) => {
+                    tracing::warn!(dataset = %dataset.name, "{}", e);
+                    rejected.insert(dataset.name.clone(), modified);
+                }
+                Err(e) => tracing::error!(dataset = %dataset.name, error = %e, "Reload panicked"),
+            }
+        }
+    }
+}
+
+/// The dataset a command should use: its `dataset` option, then the channel's default,
+/// then the guild's default, then the bot's default.
+pub fn resolve(ctx: Context<'_>, name: Option<&str>) -> Result<Arc<Dataset>, String> {
+    let datasets = &ctx.data().datasets;
+    if let Some(name) = name {
+        return datasets.get(name).ok_or_else(|| unknown(datasets, name));
+    }
+    let default = settings::get_dataset(ctx.guild_id(), ctx.channel_id()).map_err(|e| e.to_string())?;
+    // A default naming a dataset that was since unregistered falls back to the bot's default.
+    Ok(default
+        .and_then(|name| datasets.get(&name))
+        .unwrap_or_else(|| datasets.default()))
+}
+
+/// The dataset to autocomplete from, taking the `dataset` option the user has already filled in into account.
+pub fn resolve_for_autocomplete(ctx: Context<'_>) -> Arc<Dataset> {
+    let option = match ctx {
+        poise::Context::Application(ctx) => ctx
+            .interaction
+            .data
+            .options
+            .iter()
+            .find(|option| option.name == "dataset")
+            .and_then(|option| option.value.as_str().map(|value| value.to_owned())),
+        poise::Context::Prefix(_) => None,
+    };
+    resolve(ctx, option.as_deref()).unwrap_or_else(|_| ctx.data().datasets.default())
+}
+
+pub fn unknown(datasets: &Datasets, name: &str) -> String {
+    let names = datasets
+        .all()
+        .iter()
+        .map(|dataset| dataset.name.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    format!("There is no dataset called \"{}\". Available datasets: {}.", name, names)
+}
+
+pub async fn autocomplete_dataset<'a>(
+    ctx: Context<'a>,
+    partial: &'a str,
+) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    futures::stream::iter(ctx.data().datasets.all())
+        .filter(move |dataset| {
+            futures::future::ready(
+                dataset.name.to_lowercase().starts_with(&partial.to_lowercase())
+                    || dataset.title.to_lowercase().starts_with(&partial.to_lowercase()),
+            )
+        })
+        .map(|dataset| serenity::AutocompleteChoice::new(dataset.title.clone(), dataset.name.clone()))
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum Scope {
+    #[name = "This server"]
+    Guild,
+    #[name = "This channel"]
+    Channel,
+}
+
+/// Show or change which game dataset commands use.
+#[poise::command(slash_command, subcommands("list", "set", "clear"), subcommand_required)]
+pub async fn dataset(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List the available datasets and which one is in use here.
+#[poise::command(slash_command)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let current = resolve(ctx, None).unwrap_or_else(|_| ctx.data().datasets.default());
+    let lines = ctx
+        .data()
+        .datasets
+        .all()
+        .iter()
+        .map(|dataset| {
+            format!(
+                "`{0}`: {1}{2}",
+                dataset.name,
+                dataset.title,
+                if dataset.name == current.name { " (in use here)" } else { "" }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    ctx.send(poise::CreateReply::default().ephemeral(true).content(lines))
+        .await?;
+    Ok(())
+}
+
+/// Set the default dataset for this server or channel.
+#[poise::command(slash_command, guild_only, check = "crate::permissions::guild_admin")]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Dataset to use by default."]
+    #[autocomplete = "autocomplete_dataset"]
+    dataset: String,
+    #[description = "Whether to set it for the whole server or only this channel (defaults to the server)."]
+    scope: Option<Scope>,
+) -> Result<(), Error> {
+    let Some(dataset) = ctx.data().datasets.get(&dataset) else {
+        return crate::dex::reply_error(ctx, unknown(&ctx.data().datasets, &dataset)).await;
+    };
+    let scope_name = match scope.unwrap_or(Scope::Guild) {
+        Scope::Guild => {
+            settings::set_guild_dataset(ctx.guild_id().unwrap(), Some(&dataset.name))?;
+            "this server"
+        }
+        Scope::Channel => {
+            settings::set_channel_dataset(ctx.channel_id(), Some(&dataset.name))?;
+            "this channel"
+        }
+    };
+    ctx.send(
+        poise::CreateReply::default()
+            .ephemeral(true)
+            .content(format!("Commands in {} now use {} by default.", scope_name, dataset.title)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Go back to the bot's default dataset for this server or channel.
+#[poise::command(slash_command, guild_only, check = "crate::permissions::guild_admin")]
+pub async fn clear(
+    ctx: Context<'_>,
+    #[description = "Whether to clear the server's or this channel's default (defaults to the server)."]
+    scope: Option<Scope>,
+) -> Result<(), Error> {
+    let scope_name = match scope.unwrap_or(Scope::Guild) {
+        Scope::Guild => {
+            settings::set_guild_dataset(ctx.guild_id().unwrap(), None)?;
+            "this server"
+        }
+        Scope::Channel => {
+            settings::set_channel_dataset(ctx.channel_id(), None)?;
+            "this channel"
+        }
+    };
+    ctx.send(
+        poise::CreateReply::default()
+            .ephemeral(true)
+            .content(format!("Cleared the default dataset of {}.", scope_name)),
+    )
+    .await?;
+    Ok(())
+}
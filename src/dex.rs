@@ -4,33 +4,40 @@ pub mod tutor;
 pub mod levelup;
 pub mod type_effectiveness;
 pub mod autocomplete;
+pub mod stats;
+pub mod sprite;
 
 use poise::serenity_prelude as serenity;
-use crate::{Context, Error};
+use rusqlite::OptionalExtension;
+use crate::{analytics, card, sprites, Context, Error};
+use crate::datasets::{self, Dataset};
+use crate::guild_config::{DexStyle, GuildConfig, Labels};
+use crate::sprites::Variant;
+use stats::RankAmong;
 
-struct Pokemon {
-    id: u16,
-    pokedex_id: Option<u16>,
-    name: String,
-    internal_name: Option<String>,
-    base_hp: u8,
-    base_atk: u8,
-    base_def: u8,
-    base_spa: u8,
-    base_spd: u8,
-    base_spe: u8,
-    base_total: u16,
-    type1: Option<u16>,
-    type1_name: Option<String>,
-    type2: Option<u16>,
-    type2_name: Option<String>,
-    egg_group1: Option<u16>,
-    egg_group1_name: Option<String>,
-    egg_group2: Option<u16>,
-    egg_group2_name: Option<String>,
-    item1_name: Option<String>,
-    item2_name: Option<String>,
-    sprite: Option<String>,
+pub struct Pokemon {
+    pub id: u16,
+    pub pokedex_id: Option<u16>,
+    pub name: String,
+    pub internal_name: Option<String>,
+    pub base_hp: u8,
+    pub base_atk: u8,
+    pub base_def: u8,
+    pub base_spa: u8,
+    pub base_spd: u8,
+    pub base_spe: u8,
+    pub base_total: u16,
+    pub type1: Option<u16>,
+    pub type1_name: Option<String>,
+    pub type2: Option<u16>,
+    pub type2_name: Option<String>,
+    pub egg_group1: Option<u16>,
+    pub egg_group1_name: Option<String>,
+    pub egg_group2: Option<u16>,
+    pub egg_group2_name: Option<String>,
+    pub item1_name: Option<String>,
+    pub item2_name: Option<String>,
+    pub sprite: Option<String>,
 }
 
 impl Pokemon {
@@ -38,8 +45,8 @@ impl Pokemon {
         Pokemon {
             id: 27, // In rowedex.db, the None pokemon has an id of 27
             pokedex_id: Some(0),
-            name: "None".to_string(),
-            internal_name: Some("SPECIES_NONE".to_string()),
+            name: "None".to_owned(),
+            internal_name: Some("SPECIES_NONE".to_owned()),
             base_hp: 0,
             base_atk: 0,
             base_def: 0,
@@ -57,7 +64,70 @@ impl Pokemon {
             egg_group2_name: None,
             item1_name: None,
             item2_name: None,
-            sprite: Some("https://raw.githubusercontent.com/BelialClover/RoweRepo/main/graphics/pokemon/question_mark/circled/front.png".to_string()) 
+            sprite: None,
+        }
+    }
+
+    /// Base stats in HP, Atk, Def, SpA, SpD, Spe order.
+    pub fn base_stats(&self) -> [u8; 6] {
+        [
+            self.base_hp,
+            self.base_atk,
+            self.base_def,
+            self.base_spa,
+            self.base_spd,
+            self.base_spe,
+        ]
+    }
+}
+
+/// Buttons under a dex reply, in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum DexButton {
+    #[name = "Type Effectiveness"]
+    TypeEffectiveness,
+    #[name = "Level-Up"]
+    LevelUp,
+    #[name = "HM/TM"]
+    HmTm,
+    #[name = "Tutor"]
+    Tutor,
+    #[name = "Egg Moves"]
+    EggMoves,
+    #[name = "Stat Ranges"]
+    StatRanges,
+}
+
+impl DexButton {
+    pub const ALL: [DexButton; 6] = [
+        DexButton::TypeEffectiveness,
+        DexButton::LevelUp,
+        DexButton::HmTm,
+        DexButton::Tutor,
+        DexButton::EggMoves,
+        DexButton::StatRanges,
+    ];
+
+    /// Start of the button's custom id, which event_controller dispatches on.
+    pub fn id(self) -> &'static str {
+        match self {
+            DexButton::TypeEffectiveness => "typeeffectiveness_btn",
+            DexButton::LevelUp => "levelup_btn",
+            DexButton::HmTm => "hmtm_btn",
+            DexButton::Tutor => "tutor_btn",
+            DexButton::EggMoves => "eggmoves_btn",
+            DexButton::StatRanges => "stats_btn",
+        }
+    }
+
+    fn label(self, labels: &Labels) -> &'static str {
+        match self {
+            DexButton::TypeEffectiveness => labels.type_effectiveness,
+            DexButton::LevelUp => labels.level_up,
+            DexButton::HmTm => labels.hm_tm,
+            DexButton::Tutor => labels.tutor,
+            DexButton::EggMoves => labels.egg_moves,
+            DexButton::StatRanges => labels.stat_ranges,
         }
     }
 }
@@ -72,51 +142,74 @@ pub async fn dex(
     #[description = "Retrieve information about a Pokemon from the server PokeDex."]
     #[autocomplete = "autocomplete::autocomplete_pokemon"]
     pokemon: String,
+    #[description = "Game dataset to look the Pokemon up in."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+    #[description = "Pokemon the stats are ranked against. Defaults to all of them."]
+    rank_among: Option<RankAmong>,
 ) -> Result<(), Error> {
-    // Due to how types are handled for autocomplete value parameters, pokemon id (u16) gets passed in as a String.
-    // Hence the need to parse the string for u16.
-    let pokemon_id: &Option<u16> = &pokemon.parse::<u16>().map_or_else(|_| None, |id| Some(id));
-    let pokemon_result: Result<Pokemon, rusqlite::Error>;
-    // If the user did not click an autocomplete option and instead manually typed
-    // the name, this if statement will catch that case and try the query again by name.
-    if pokemon_id.is_some() {
-        pokemon_result = get_pokemon_by_id(&pokemon_id.expect("Pokemon ID incorrectly parsed."));
-    }
-    else {
-        pokemon_result = get_pokemon_by_name(&pokemon);
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let Some(p) = resolve_pokemon(&dataset, &pokemon)? else {
+        return reply_error(ctx, unknown_pokemon(&pokemon)).await;
+    };
+    reply_pokemon(ctx, &dataset, p, rank_among.unwrap_or(RankAmong::All)).await
+}
+/// Sends the dex reply of a Pokemon, as an embed or stat card depending on the guild's style.
+pub async fn reply_pokemon(ctx: Context<'_>, dataset: &Dataset, p: Pokemon, rank_among: RankAmong) -> Result<(), Error> {
+    let config = GuildConfig::get(ctx.guild_id())?;
+    let labels = config.language.labels();
+    analytics::target(ctx, dataset, p.id).await;
+    let abilities = get_abilities(dataset, &p.id).unwrap_or(vec![]);
+    let mut msg = poise::CreateReply::default().ephemeral(!config.public_replies);
+    let mut embed = serenity::CreateEmbed::new()
+        .title(
+            format!(
+                "#{0}: {1}", 
+                p.pokedex_id.unwrap_or(0), 
+                p.name
+            )
+        )
+        .colour(get_color_from_type(p.type1_name.as_deref().unwrap_or("")));
+    if let Some(url) = dataset.external_link(p.internal_name.as_deref().unwrap_or("")) {
+        embed = embed.url(url);
     }
-    let msg = pokemon_result.and_then(|p| { 
-        Ok(poise::CreateReply::default()
-            .ephemeral(true)
-            .embed(serenity::CreateEmbed::new()
-                .title(
-                    format!(
-                        "#{0}: {1}", 
-                        p.pokedex_id.unwrap_or(0), 
-                        p.name
-                    )
-                )
-                .url(
-                    format!(
-                        "https://ydarissep.github.io/R.O.W.E-Pokedex/?species={0}&table=speciesTable",
-                        p.internal_name.as_ref().unwrap_or(&"".to_string())
-                    )
-                )
-                .colour(get_color_from_type(&p.type1_name.as_ref().unwrap_or(&"".to_string())))
-                .thumbnail(p.sprite.unwrap())
+    let card = match config.style {
+        DexStyle::Card => card::for_pokemon(dataset, &p, &abilities, labels, Variant::Front).await,
+        DexStyle::Embed => None,
+    };
+    let components = components(&config, dataset, &p, Variant::Front).await;
+    let embed = match card {
+        Some(png) => {
+            msg = msg.attachment(serenity::CreateAttachment::bytes(png, card::FILE_NAME));
+            embed.image(format!("attachment://{}", card::FILE_NAME))
+        }
+        None => {
+            let (sprite_url, attachment) =
+                sprites::find(dataset, p.sprite.as_deref(), Variant::Front).for_embed().await;
+            msg = msg.attachment(attachment);
+            let (pool, note) = match rank_among {
+                RankAmong::All => (stats::get_base_stats(dataset, false)?, labels.ranked_among_all),
+                RankAmong::FullyEvolved => (stats::get_base_stats(dataset, true)?, labels.ranked_among_fully_evolved),
+            };
+            let stat_bars = stats::stat_bars(&p, &pool, note);
+            embed
+                .thumbnail(sprite_url)
                 .field(
-                    "Types",
-                    &p.type1_name.as_ref().map_or(
-                        format!("{}", "None"),
+                    labels.types,
+                    p.type1_name.as_ref().map_or(
+                        labels.none.to_owned(),
                         |type1_name| {
                             if p.type1.unwrap_or(0) == p.type2.unwrap_or(0) {
-                                format!("{}", type1_name)
+                                type1_name.to_string()
                             }
                             else {
                                 format!(
                                     "{0}, {1}", 
                                     type1_name,
-                                    &p.type2_name.as_ref().unwrap_or(&"".to_string())
+                                    &p.type2_name.as_ref().unwrap_or(&"".to_owned())
                                 )
                             }
                         }
@@ -124,21 +217,20 @@ pub async fn dex(
                     true 
                 )
                 .field(
-                    "Abilities",
-                    get_abilities(&p.id)
-                        .unwrap_or(vec![])
+                    labels.abilities,
+                    abilities
                         .iter()
                         .map(|row| format!(
                             "{0}:\t{1}\n",
-                            row.name.to_string(),
-                            row.description.to_string()
+                            row.name,
+                            row.description
                         ))
                         .collect::<Vec<String>>()
                         .concat(),
                     false
                 )
                 .field(
-                    "Held Items",
+                    labels.held_items,
                     p.item1_name
                         .map_or("".to_owned(), |item| "50% ".to_owned() + &item)
                         .to_string()
@@ -149,118 +241,153 @@ pub async fn dex(
                     false
                 )
                 .field(
-                    "Egg Groups",
-                    &p.egg_group1_name.as_ref().map_or(
-                        format!("{}", "None"),
+                    labels.egg_groups,
+                    p.egg_group1_name.as_ref().map_or(
+                        labels.none.to_owned(),
                         |egg_group1_name| {
                             if p.egg_group1.unwrap_or(0) == p.egg_group2.unwrap_or(0) {
-                                format!("{}", egg_group1_name)
+                                egg_group1_name.to_string()
                             }
                             else {
                                 format!(
                                     "{0}, {1}", 
                                     egg_group1_name,
-                                    &p.egg_group2_name.as_ref().unwrap_or(&"".to_string())
+                                    &p.egg_group2_name.as_ref().unwrap_or(&"".to_owned())
                                 )
                             }
                         }
                     ),
                     true 
                 )
-                .field(
-                    "Stats",
-                    format!("```c\nHP: \t{0}\nAtk:\t{1}\nDef:\t{2}\nSpA:\t{3}\nSpD:\t{4}\nSpe:\t{5}\nBST:\t{6}```",
-                        p.base_hp,
-                        p.base_atk,
-                        p.base_def,
-                        p.base_spa,
-                        p.base_spd,
-                        p.base_spe,
-                        p.base_total
-                    ),
-                    false
-                )
+                .field(labels.stats, stat_bars, false)
+        }
+    };
+    let msg = msg
+        .embed(embed)
+        .components(components);
+    ctx.send(msg).await?;
+    Ok(())
+}
+/// The buttons under a dex reply, and the sprite menu with the shown sprite selected.
+pub async fn components(config: &GuildConfig, dataset: &Dataset, p: &Pokemon, shown: Variant) -> Vec<serenity::CreateActionRow> {
+    let labels = config.language.labels();
+    // Discord allows at most 5 buttons per row.
+    let mut rows: Vec<serenity::CreateActionRow> = config
+        .buttons
+        .chunks(5)
+        .map(|row| {
+            serenity::CreateActionRow::Buttons(
+                row.iter()
+                    .map(|button| {
+                        serenity::CreateButton::new(button_id(button.id(), dataset, p.id))
+                            .style(serenity::ButtonStyle::Secondary)
+                            .label(button.label(labels))
+                    })
+                    .collect(),
             )
-            .components(vec![
-                serenity::CreateActionRow::Buttons(vec![
-                    serenity::CreateButton::new(&("typeeffectiveness_btn__".to_owned() + &p.id.to_string()))
-                        .style(serenity::ButtonStyle::Secondary)
-                        .label("Type Effectiveness"),
-                    serenity::CreateButton::new(&("levelup_btn__".to_owned() + &p.id.to_string()))
-                        .style(serenity::ButtonStyle::Secondary)
-                        .label("Level-Up"),
-                    serenity::CreateButton::new(&("hmtm_btn__".to_owned() + &p.id.to_string()))
-                        .style(serenity::ButtonStyle::Secondary)
-                        .label("HM/TM"),
-                    serenity::CreateButton::new(&("tutor_btn__".to_owned() + &p.id.to_string()))
-                        .style(serenity::ButtonStyle::Secondary)
-                        .label("Tutor"),
-                    serenity::CreateButton::new(&("eggmoves_btn__".to_owned() + &p.id.to_string()))
-                        .style(serenity::ButtonStyle::Secondary)
-                        .label("Egg Moves")
-                ])
-            ])
-        )
-    });
-    ctx.send(msg?).await?;
+        })
+        .collect();
+    rows.extend(sprite::sprite_menu(dataset, p, shown, labels).await);
+    rows
+}
+/// Custom id of a dex button, e.g. "levelup_btn__28__rowe".
+/// The dataset is part of it so the button keeps using the dataset the embed was made from.
+pub fn button_id(button: &str, dataset: &Dataset, pokemon_id: u16) -> String {
+    format!("{0}__{1}__{2}", button, pokemon_id, dataset.name)
+}
+/// Replies to the end-user with a message only they can see, e.g. to confirm a setting was changed.
+pub async fn reply(ctx: Context<'_>, content: String) -> Result<(), Error> {
+    ctx.send(poise::CreateReply::default().ephemeral(true).content(content))
+        .await?;
     Ok(())
 }
-fn get_pokemon_by_id(id: &u16) -> Result<Pokemon, rusqlite::Error> {
-    let sql = match std::fs::read_to_string("./src/dex/queries/get_pokemon.sql") {
+/// Replies to the end-user with a message explaining what was wrong with their input.
+pub async fn reply_error(ctx: Context<'_>, message: String) -> Result<(), Error> {
+    reply(ctx, message).await
+}
+/// Reads one of the bot's query files, e.g. "./src/dex/queries/get_abilities.sql".
+/// They ship with the bot, so a missing one is a broken install and panics.
+pub fn read_query(path: &str) -> String {
+    match std::fs::read_to_string(path) {
         Ok(contents) => contents,
         Err(e) => {
-            println!("{}", e.to_string());
+            tracing::error!(query = path, error = %e, "Failed to read query file");
             panic!()
         }
-    };
-    let conn = rusqlite::Connection::open("rowedex.db").unwrap();
-    let mut stmt = conn.prepare(&sql).unwrap();
-    stmt.query_row([id], |row| {
-        let default_pokemon = Pokemon::default();
-        Ok(Pokemon {
-            id: row.get(0).unwrap_or(default_pokemon.id),
-            pokedex_id: row.get(1).unwrap_or(default_pokemon.pokedex_id),
-            name: row.get(2).unwrap_or(default_pokemon.name),
-            internal_name: row.get(3).unwrap_or(default_pokemon.internal_name),
-            base_hp: row.get(4).unwrap_or(default_pokemon.base_hp),
-            base_atk: row.get(5).unwrap_or(default_pokemon.base_atk),
-            base_def: row.get(6).unwrap_or(default_pokemon.base_def),
-            base_spa: row.get(7).unwrap_or(default_pokemon.base_spa),
-            base_spd: row.get(8).unwrap_or(default_pokemon.base_spd),
-            base_spe: row.get(9).unwrap_or(default_pokemon.base_spe),
-            base_total: row.get(10).unwrap_or(default_pokemon.base_total),
-            type1: row.get(11).unwrap_or(default_pokemon.type1),
-            type1_name: row.get(12).unwrap_or(default_pokemon.type1_name),
-            type2: row.get(13).unwrap_or(default_pokemon.type2),
-            type2_name: row.get(14).unwrap_or(default_pokemon.type2_name),
-            egg_group1: row.get(15).unwrap_or(default_pokemon.egg_group1),
-            egg_group1_name: row.get(16).unwrap_or(default_pokemon.egg_group1_name),
-            egg_group2: row.get(17).unwrap_or(default_pokemon.egg_group2),
-            egg_group2_name: row.get(18).unwrap_or(default_pokemon.egg_group2_name),
-            item1_name: row.get(19).unwrap_or(default_pokemon.item1_name),
-            item2_name: row.get(20).unwrap_or(default_pokemon.item2_name),
-            sprite: row.get(21).unwrap_or(default_pokemon.sprite),
+    }
+}
+/// Discord rejects embed fields longer than this.
+pub const FIELD_LIMIT: usize = 1024;
+/// Joins names with commas, ending with "... and N more" where the next name would go past `limit`.
+pub fn truncate_list(names: &[&str], limit: usize) -> String {
+    if names.is_empty() {
+        return "None".to_owned();
+    }
+    let mut output = String::new();
+    for (i, name) in names.iter().enumerate() {
+        let more = format!("... and {} more", names.len() - i);
+        if i > 0 {
+            output.push_str(", ");
+        }
+        if output.len() + name.len() + 2 + more.len() > limit {
+            output.push_str(&more);
+            break;
+        }
+        output.push_str(name);
+    }
+    output
+}
+/// Cuts a field's value at its last whole line that fits.
+pub fn truncate_lines(value: String) -> String {
+    if value.chars().count() <= FIELD_LIMIT {
+        return value;
+    }
+    let mut truncated = String::new();
+    for line in value.lines() {
+        if truncated.chars().count() + line.chars().count() + 2 > FIELD_LIMIT {
+            break;
+        }
+        truncated.push_str(line);
+        truncated.push('\n');
+    }
+    truncated.push('…');
+    truncated
+}
+/// Looks up the Pokemon picked from autocomplete, or typed out by name. None if there is no such Pokemon.
+pub fn resolve_pokemon(dataset: &Dataset, pokemon: &str) -> Result<Option<Pokemon>, rusqlite::Error> {
+    // Due to how types are handled for autocomplete value parameters, pokemon id (u16) gets passed in as a String.
+    // Hence the need to parse the string for u16.
+    // If the user did not click an autocomplete option and instead manually typed
+    // the name, fall back to searching by name.
+    match pokemon.parse::<u16>() {
+        Ok(pokemon_id) => query_pokemon(dataset, "./src/dex/queries/get_pokemon.sql", pokemon_id),
+        Err(_) => query_pokemon(dataset, "./src/dex/queries/get_pokemon_by_name.sql", pokemon),
+    }
+}
+pub fn get_pokemon_by_id(dataset: &Dataset, id: &u16) -> Result<Pokemon, rusqlite::Error> {
+    query_pokemon(dataset, "./src/dex/queries/get_pokemon.sql", id).map(|pokemon| pokemon.unwrap_or_else(Pokemon::default))
+}
+/// Reply to a pokemon parameter that matched nothing.
+pub fn unknown_pokemon(pokemon: &str) -> String {
+    format!("Could not find the Pokemon \"{}\".", pokemon)
+}
+// Intended as the backup in case the id search fails
+pub fn get_pokemon_by_name(dataset: &Dataset, name: &str) -> Result<Pokemon, rusqlite::Error> {
+    query_pokemon(dataset, "./src/dex/queries/get_pokemon_by_name.sql", name).map(|pokemon| {
+        pokemon.unwrap_or_else(|| {
+            let mut default_pokemon = Pokemon::default();
+            default_pokemon.name = "Could not find \"".to_owned() + name + "\"";
+            default_pokemon
         })
     })
-    .or_else(|err| match err {
-        rusqlite::Error::QueryReturnedNoRows => {
-            Ok(Pokemon::default())
-        },
-        _ => Err(err)
-    })
 }
-// Intended as the backup in case the id search fails
-fn get_pokemon_by_name(name: &str) -> Result<Pokemon, rusqlite::Error> {
-    let sql = match std::fs::read_to_string("./src/dex/queries/get_pokemon_by_name.sql") {
-        Ok(contents) => contents,
-        Err(e) => {
-            println!("{}", e.to_string());
-            panic!()
-        }
-    };
-    let conn = rusqlite::Connection::open("rowedex.db").unwrap();
+/// Runs get_pokemon.sql or get_pokemon_by_name.sql.
+fn query_pokemon(dataset: &Dataset, path: &str, param: impl rusqlite::ToSql) -> Result<Option<Pokemon>, rusqlite::Error> {
+    let sql = read_query(path);
+    let _timer = crate::metrics::time_query(path);
+    let conn = dataset.open();
     let mut stmt = conn.prepare(&sql).unwrap();
-    stmt.query_row([name], |row| {
+    stmt.query_row([param], |row| {
         let default_pokemon = Pokemon::default();
         Ok(Pokemon {
             id: row.get(0).unwrap_or(default_pokemon.id),
@@ -287,35 +414,23 @@ fn get_pokemon_by_name(name: &str) -> Result<Pokemon, rusqlite::Error> {
             sprite: row.get(21).unwrap_or(default_pokemon.sprite),
         })
     })
-    .or_else(|err| match err {
-        rusqlite::Error::QueryReturnedNoRows => {
-            let mut default_pokemon = Pokemon::default();
-            default_pokemon.name = "Could not find \"".to_string() + name + "\"";
-            Ok(default_pokemon)
-        },
-        _ => Err(err)
-    }) 
+    .optional()
 }
 
 // Ability field
-struct Ability {
-    name: String,
-    description: String,
+pub struct Ability {
+    pub name: String,
+    pub description: String,
 }
-fn get_abilities(pokemon_id: &u16) -> Result<Vec<Ability>, rusqlite::Error> {
-    let sql = match std::fs::read_to_string("./src/dex/queries/get_abilities.sql") {
-        Ok(contents) => contents,
-        Err(e) => {
-            println!("{}", e.to_string());
-            panic!()
-        }
-    };
-    let conn = rusqlite::Connection::open("rowedex.db").unwrap();
+pub fn get_abilities(dataset: &Dataset, pokemon_id: &u16) -> Result<Vec<Ability>, rusqlite::Error> {
+    let sql = read_query("./src/dex/queries/get_abilities.sql");
+    let _timer = crate::metrics::time_query("./src/dex/queries/get_abilities.sql");
+    let conn = dataset.open();
     let mut stmt = conn.prepare(&sql).unwrap();
     let mut rows = match stmt.query([pokemon_id]) {
         Ok(res) => res,
         Err(e) => {
-            println!("ERROR (Query): {0}", e.to_string());
+            tracing::error!(query = "./src/dex/queries/get_abilities.sql", error = %e, "Query failed");
             return Err(e);
         }
     };
@@ -329,8 +444,30 @@ fn get_abilities(pokemon_id: &u16) -> Result<Vec<Ability>, rusqlite::Error> {
     Ok(abilities)
 }
 
-fn get_color_from_type(pokemon_type: &String) -> serenity::model::Color {
-    match pokemon_type.as_ref() {
+// Where a Pokemon can be found in the wild
+pub struct Location {
+    pub name: String,
+    /// Encounter method, e.g. Day, Night or Surfing.
+    pub method: String,
+    pub chance: u8,
+}
+pub fn get_locations(dataset: &Dataset, pokemon_id: &u16) -> Result<Vec<Location>, rusqlite::Error> {
+    let sql = read_query("./src/dex/queries/get_locations.sql");
+    let _timer = crate::metrics::time_query("./src/dex/queries/get_locations.sql");
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([pokemon_id], |row| {
+        Ok(Location {
+            name: row.get(0)?,
+            method: row.get(1)?,
+            chance: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn get_color_from_type(pokemon_type: &str) -> serenity::model::Color {
+    match pokemon_type {
         "Normal" => serenity::model::Color::new(10329457),
         "Fire" => serenity::model::Color::new(15630640),
         "Water" => serenity::model::Color::new(6525168),
@@ -352,3 +489,22 @@ fn get_color_from_type(pokemon_type: &String) -> serenity::model::Color {
         _ => serenity::model::Color::LIGHTER_GREY,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::Datasets;
+
+    #[test]
+    fn resolves_pokemon_by_id_or_name() {
+        let _ = crate::config::init(&[]);
+        let datasets = Datasets::load().unwrap();
+        let dataset = datasets.default();
+        let garchomp = resolve_pokemon(&dataset, "garchomp").unwrap().unwrap();
+        assert_eq!(garchomp.name, "Garchomp");
+        let by_id = resolve_pokemon(&dataset, &garchomp.id.to_string()).unwrap().unwrap();
+        assert_eq!(by_id.name, "Garchomp");
+        assert!(resolve_pokemon(&dataset, "Garchompy").unwrap().is_none());
+        assert!(resolve_pokemon(&dataset, "65000").unwrap().is_none());
+    }
+}
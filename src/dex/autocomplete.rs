@@ -1,5 +1,6 @@
 use poise::serenity_prelude as serenity;
-use crate::{Context, Error};
+use crate::{metrics, Context, Error};
+use crate::datasets::{self, Dataset};
 use futures::{Stream, StreamExt};
 
 // Autocomplete pokemon
@@ -9,13 +10,15 @@ struct PokemonAutocomplete {
 }
 /// Uses end-user's partial input into dex command for autocompleting pokemon
 pub async fn autocomplete_pokemon<'a>(
-    _ctx: Context<'_>,
+    ctx: Context<'_>,
     partial: &'a str,
 ) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    let _timer = metrics::AUTOCOMPLETE_DURATION.with_label_values(&["pokemon"]).start_timer();
     // Retrieve a list of Pokemon based on the passed in partial text
+    let dataset = datasets::resolve_for_autocomplete(ctx);
     let mons: Vec<PokemonAutocomplete> =
-        get_pokemon_autocomplete(partial.to_string()).unwrap_or_else(|e| {
-            println!("{}", e.to_string());
+        get_pokemon_autocomplete(&dataset, partial.to_owned()).unwrap_or_else(|e| {
+            tracing::error!(error = %e, "Pokemon autocomplete failed");
             vec![]
         });
     futures::stream::iter(mons).map(move |pokemon| {
@@ -23,10 +26,11 @@ pub async fn autocomplete_pokemon<'a>(
     })
 }
 fn get_pokemon_autocomplete(
+    dataset: &Dataset,
     name_partial: String,
 ) -> Result<Vec<PokemonAutocomplete>, Error> {
     let mut mons: Vec<PokemonAutocomplete> = Vec::new();
-    let conn = rusqlite::Connection::open("rowedex.db").unwrap();
+    let conn = dataset.open();
     let mut stmt = conn.prepare(
         "select [id], [name] from pokemon where [name] like ?1 limit 25",
     )?;
@@ -39,3 +43,86 @@ fn get_pokemon_autocomplete(
     }
     Ok(mons)
 }
+
+// Autocomplete moves
+struct MoveAutocomplete {
+    id: u16,
+    name: String,
+}
+/// Uses end-user's partial input for autocompleting moves
+pub async fn autocomplete_move<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    let _timer = metrics::AUTOCOMPLETE_DURATION.with_label_values(&["move"]).start_timer();
+    let dataset = datasets::resolve_for_autocomplete(ctx);
+    let moves: Vec<MoveAutocomplete> =
+        get_move_autocomplete(&dataset, partial.to_owned()).unwrap_or_else(|e| {
+            tracing::error!(error = %e, "Move autocomplete failed");
+            vec![]
+        });
+    futures::stream::iter(moves).map(move |m| {
+        serenity::AutocompleteChoice::new(m.name, m.id.to_string())
+    })
+}
+fn get_move_autocomplete(
+    dataset: &Dataset,
+    name_partial: String,
+) -> Result<Vec<MoveAutocomplete>, Error> {
+    let mut moves: Vec<MoveAutocomplete> = Vec::new();
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(
+        "select [id], [name] from moves where [name] like ?1 limit 25",
+    )?;
+    let mut rows = stmt.query([name_partial + "%"])?;
+    while let Some(row) = rows.next()? {
+        moves.push(MoveAutocomplete {
+            id: row.get(0)?,
+            name: row.get(1)?,
+        });
+    }
+    Ok(moves)
+}
+
+// Autocomplete types and egg groups, by name
+/// Uses end-user's partial input for autocompleting types
+pub async fn autocomplete_type<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    let _timer = metrics::AUTOCOMPLETE_DURATION.with_label_values(&["type"]).start_timer();
+    let dataset = datasets::resolve_for_autocomplete(ctx);
+    let names = get_name_autocomplete(&dataset, "types", partial).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Type autocomplete failed");
+        vec![]
+    });
+    futures::stream::iter(names).map(|name| serenity::AutocompleteChoice::new(name.clone(), name))
+}
+/// Uses end-user's partial input for autocompleting egg groups
+pub async fn autocomplete_egg_group<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Stream<Item = serenity::AutocompleteChoice> + 'a {
+    let _timer = metrics::AUTOCOMPLETE_DURATION.with_label_values(&["egg_group"]).start_timer();
+    let dataset = datasets::resolve_for_autocomplete(ctx);
+    let names = get_name_autocomplete(&dataset, "egg_groups", partial).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Egg group autocomplete failed");
+        vec![]
+    });
+    futures::stream::iter(names).map(|name| serenity::AutocompleteChoice::new(name.clone(), name))
+}
+fn get_name_autocomplete(
+    dataset: &Dataset,
+    table: &'static str,
+    name_partial: &str,
+) -> Result<Vec<String>, Error> {
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&format!(
+        "select distinct [name] from {} where [name] like ?1 order by [name] limit 25",
+        table
+    ))?;
+    let names = stmt
+        .query_map([format!("{}%", name_partial)], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    Ok(names)
+}
@@ -1,3 +1,4 @@
+use crate::datasets::Dataset;
 use crate::Error;
 use poise::serenity_prelude as serenity;
 
@@ -7,22 +8,17 @@ pub struct LevelupMove {
 }
 
 pub fn get_levelup_sets(
+    dataset: &Dataset,
     pokemon_id: &u16,
 ) -> Result<Vec<LevelupMove>, rusqlite::Error> {
-    let sql = match std::fs::read_to_string("./src/dex/queries/get_levelup_set.sql")
-    {
-        Ok(contents) => contents,
-        Err(e) => {
-            println!("{}", e.to_string());
-            panic!()
-        }
-    };
-    let conn = rusqlite::Connection::open("rowedex.db").unwrap();
+    let sql = crate::dex::read_query("./src/dex/queries/get_levelup_set.sql");
+    let _timer = crate::metrics::time_query("./src/dex/queries/get_levelup_set.sql");
+    let conn = dataset.open();
     let mut stmt = conn.prepare(&sql).unwrap();
     let mut rows = match stmt.query([pokemon_id]) {
         Ok(res) => res,
         Err(e) => {
-            println!("ERROR (Query): {0}", e.to_string());
+            tracing::error!(query = "./src/dex/queries/get_levelup_set.sql", error = %e, "Query failed");
             return Err(e);
         }
     };
@@ -39,12 +35,13 @@ pub fn get_levelup_sets(
 pub async fn levelup_component(
     ctx: serenity::Context,
     i: &serenity::ComponentInteraction,
+    dataset: &Dataset,
     pokemon_id: u16,
 ) -> Result<(), Error> {
-    let content = get_levelup_sets(&pokemon_id).map_or_else(
-        |e| format!("{}", e.to_string()),
+    let content = get_levelup_sets(dataset, &pokemon_id).map_or_else(
+        |e| format!("{}", e),
         |rows| {
-            let mut output: String = "Level-Up moves\n".to_string();
+            let mut output: String = "Level-Up moves\n".to_owned();
             for row in rows {
                 output.push_str(&row.move_name.to_string());
                 output.push_str(" (Level ");
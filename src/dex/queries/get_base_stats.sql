@@ -0,0 +1,25 @@
+select 
+    p.name,
+    p.base_hp,
+    p.base_atk,
+    p.base_def,
+    p.base_spa,
+    p.base_spd,
+    p.base_spe
+from 
+    pokemon p
+where
+    p.base_total > 0
+    -- When ?1 is set, skip any Pokemon that can still evolve. Mega evolving does not count.
+    and (?1 = 0 or not exists (
+        select 1
+        from
+            pokemon_evolution_relationships per
+        inner join
+            evolution_methods em on (em.id = per.evolution_method)
+        where
+            per.pokemon = p.id
+            and em.internal_name not in ('EVO_MEGA_EVOLUTION', 'EVO_MOVE_MEGA_EVOLUTION')
+    ))
+order by
+    p.pokedex_id asc;
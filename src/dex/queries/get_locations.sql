@@ -0,0 +1,16 @@
+select
+    l.name,
+    em.name,
+    s.chance
+from
+    sightings s
+inner join
+    locations l on (l.id = s.location)
+inner join
+    encounter_methods em on (em.id = s.encounter_method)
+where
+    s.pokemon = ?1
+order by
+    s.chance desc,
+    l.name asc,
+    em.id asc
@@ -0,0 +1,88 @@
+use poise::serenity_prelude as serenity;
+use crate::datasets::Dataset;
+use crate::guild_config::{GuildConfig, Labels};
+use crate::sprites::{self, Variant};
+use crate::{card, Error};
+use super::Pokemon;
+
+/// Start of the sprite menu's custom id, which event_controller dispatches on.
+pub const MENU_ID: &str = "sprite_menu";
+
+/// Menu switching the dex reply's sprite between the variants that can be found, if there is more than one.
+pub async fn sprite_menu(dataset: &Dataset, p: &Pokemon, selected: Variant, labels: &Labels) -> Option<serenity::CreateActionRow> {
+    let variants = sprites::available(dataset, p.sprite.as_deref(), &Variant::SHOWN).await;
+    if variants.len() < 2 {
+        return None;
+    }
+    let options = variants
+        .into_iter()
+        .map(|variant| {
+            serenity::CreateSelectMenuOption::new(label(variant, labels), variant.id()).default_selection(variant == selected)
+        })
+        .collect();
+    Some(serenity::CreateActionRow::SelectMenu(serenity::CreateSelectMenu::new(
+        super::button_id(MENU_ID, dataset, p.id),
+        serenity::CreateSelectMenuKind::String { options },
+    )))
+}
+
+fn label(variant: Variant, labels: &Labels) -> &'static str {
+    match variant {
+        Variant::Front => labels.front,
+        Variant::Back => labels.back,
+        Variant::ShinyFront => labels.shiny_front,
+        Variant::ShinyBack => labels.shiny_back,
+        // Not in the menu.
+        Variant::Icon => "Icon",
+    }
+}
+
+/// Shows the chosen sprite in place of the current one, in the thumbnail or on the stat card.
+pub async fn sprite_component(
+    ctx: serenity::Context,
+    i: &serenity::ComponentInteraction,
+    dataset: &Dataset,
+    pokemon_id: u16,
+) -> Result<(), Error> {
+    let serenity::ComponentInteractionDataKind::StringSelect { values } = &i.data.kind else {
+        return Ok(());
+    };
+    let (Some(variant), Some(current)) = (values.first().and_then(|value| Variant::from_id(value)), i.message.embeds.first())
+    else {
+        return Ok(());
+    };
+    let p = super::get_pokemon_by_id(dataset, &pokemon_id)?;
+    let config = GuildConfig::get(i.guild_id)?;
+    let labels = config.language.labels();
+    let embed = serenity::CreateEmbed::from(current.clone());
+    // The guild may have changed style since, the reply keeps the one it was sent with.
+    let card = match current.image {
+        Some(_) => {
+            let abilities = super::get_abilities(dataset, &p.id).unwrap_or(vec![]);
+            card::for_pokemon(dataset, &p, &abilities, labels, variant).await
+        }
+        None => None,
+    };
+    let (embed, attachment) = match card {
+        Some(png) => (
+            embed.image(format!("attachment://{}", card::FILE_NAME)),
+            Some(serenity::CreateAttachment::bytes(png, card::FILE_NAME)),
+        ),
+        None => {
+            let (url, attachment) = sprites::find(dataset, p.sprite.as_deref(), variant).for_embed().await;
+            (embed.thumbnail(url), Some(attachment))
+        }
+    };
+    // Existing attachments are dropped unless they are sent again, so the previous sprite goes away.
+    i.create_response(
+        ctx,
+        serenity::CreateInteractionResponse::UpdateMessage(
+            serenity::CreateInteractionResponseMessage::new()
+                .embed(embed)
+                .components(super::components(&config, dataset, &p, variant).await)
+                .files(attachment),
+        ),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,548 @@
+use poise::serenity_prelude as serenity;
+use crate::{Context, Error};
+use crate::datasets::{self, Dataset};
+use super::{autocomplete, Pokemon};
+
+pub const MAX_IV: u8 = 31;
+pub const MAX_EV: u8 = 252;
+pub const MAX_TOTAL_EVS: u16 = 510;
+
+/// Base stats of a Pokemon, as stored in the pokemon table.
+#[derive(Clone, Copy, Debug, PartialEq, poise::ChoiceParameter)]
+pub enum Stat {
+    #[name = "HP"]
+    Hp,
+    #[name = "Attack"]
+    Atk,
+    #[name = "Defense"]
+    Def,
+    #[name = "Sp. Atk"]
+    Spa,
+    #[name = "Sp. Def"]
+    Spd,
+    #[name = "Speed"]
+    Spe,
+    #[name = "Base Stat Total"]
+    Bst,
+}
+
+impl Stat {
+    /// The six individual stats, in the order used by stat arrays throughout the bot.
+    pub const ALL: [Stat; 6] = [Stat::Hp, Stat::Atk, Stat::Def, Stat::Spa, Stat::Spd, Stat::Spe];
+
+    pub fn column(&self) -> &'static str {
+        match self {
+            Stat::Hp => "p.base_hp",
+            Stat::Atk => "p.base_atk",
+            Stat::Def => "p.base_def",
+            Stat::Spa => "p.base_spa",
+            Stat::Spd => "p.base_spd",
+            Stat::Spe => "p.base_spe",
+            Stat::Bst => "p.base_total",
+        }
+    }
+
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Stat::Hp => "HP",
+            Stat::Atk => "Atk",
+            Stat::Def => "Def",
+            Stat::Spa => "SpA",
+            Stat::Spd => "SpD",
+            Stat::Spe => "Spe",
+            Stat::Bst => "BST",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Stat> {
+        match key.to_lowercase().as_str() {
+            "hp" => Some(Stat::Hp),
+            "atk" | "attack" => Some(Stat::Atk),
+            "def" | "defense" => Some(Stat::Def),
+            "spa" | "spatk" => Some(Stat::Spa),
+            "spd" | "spdef" => Some(Stat::Spd),
+            "spe" | "speed" => Some(Stat::Spe),
+            "bst" | "total" => Some(Stat::Bst),
+            _ => None,
+        }
+    }
+
+    /// Position of the stat within a stat array. BST is not part of one.
+    pub fn index(&self) -> Option<usize> {
+        Stat::ALL.iter().position(|stat| stat == self)
+    }
+}
+
+/// Natures, in their in-game index order.
+#[derive(Clone, Copy, Debug, PartialEq, poise::ChoiceParameter)]
+pub enum Nature {
+    Hardy,
+    Lonely,
+    Brave,
+    Adamant,
+    Naughty,
+    Bold,
+    Docile,
+    Relaxed,
+    Impish,
+    Lax,
+    Timid,
+    Hasty,
+    Serious,
+    Jolly,
+    Naive,
+    Modest,
+    Mild,
+    Quiet,
+    Bashful,
+    Rash,
+    Calm,
+    Gentle,
+    Sassy,
+    Careful,
+    Quirky,
+}
+
+impl Nature {
+    /// The stat raised and the stat lowered by the nature. Neutral natures return None.
+    pub fn modifiers(&self) -> Option<(Stat, Stat)> {
+        // Natures are laid out as a 5x5 grid of (raised, lowered) over Atk, Def, Spe, SpA, SpD.
+        const ORDER: [Stat; 5] = [Stat::Atk, Stat::Def, Stat::Spe, Stat::Spa, Stat::Spd];
+        let index = *self as usize;
+        let (raised, lowered) = (ORDER[index / 5], ORDER[index % 5]);
+        if raised == lowered {
+            None
+        } else {
+            Some((raised, lowered))
+        }
+    }
+
+    /// Nature multiplier for a stat, as a percentage.
+    pub fn multiplier(&self, stat: Stat) -> u16 {
+        match self.modifiers() {
+            Some((raised, _)) if raised == stat => 110,
+            Some((_, lowered)) if lowered == stat => 90,
+            _ => 100,
+        }
+    }
+}
+
+/// Everything besides base stats that determines a Pokemon's final stats.
+#[derive(Clone, Copy, Debug)]
+pub struct Spread {
+    pub level: u8,
+    pub ivs: [u8; 6],
+    pub evs: [u8; 6],
+    pub nature: Nature,
+}
+
+impl Spread {
+    /// Builds a spread from optional command parameters. Level defaults to 100, IVs to 31,
+    /// EVs to 0 and nature to a neutral one.
+    pub fn from_options(
+        level: Option<u8>,
+        ivs: Option<&str>,
+        evs: Option<&str>,
+        nature: Option<Nature>,
+    ) -> Result<Spread, String> {
+        let spread = Spread {
+            level: level.unwrap_or(100),
+            ivs: ivs
+                .map(|ivs| parse_spread(ivs, MAX_IV, MAX_IV))
+                .transpose()?
+                .unwrap_or([MAX_IV; 6]),
+            evs: evs
+                .map(|evs| parse_spread(evs, 0, MAX_EV))
+                .transpose()?
+                .unwrap_or([0; 6]),
+            nature: nature.unwrap_or(Nature::Hardy),
+        };
+        let total_evs: u16 = spread.evs.iter().map(|ev| u16::from(*ev)).sum();
+        if total_evs > MAX_TOTAL_EVS {
+            return Err(format!(
+                "{} EVs is above the maximum of {}.",
+                total_evs, MAX_TOTAL_EVS
+            ));
+        }
+        Ok(spread)
+    }
+}
+
+/// Base stats of a Pokemon, without the rest of its dex entry.
+pub struct BaseStats {
+    pub name: String,
+    pub stats: [u8; 6],
+}
+
+impl BaseStats {
+    pub fn total(&self) -> u16 {
+        self.stats.iter().map(|stat| u16::from(*stat)).sum()
+    }
+}
+
+/// Base stats of every Pokemon in the PokeDex, optionally only those that cannot evolve any further.
+pub fn get_base_stats(dataset: &Dataset, fully_evolved_only: bool) -> Result<Vec<BaseStats>, rusqlite::Error> {
+    let sql = crate::dex::read_query("./src/dex/queries/get_base_stats.sql");
+    let _timer = crate::metrics::time_query("./src/dex/queries/get_base_stats.sql");
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map([fully_evolved_only], |row| {
+        Ok(BaseStats {
+            name: row.get(0)?,
+            stats: [
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ],
+        })
+    })?;
+    rows.collect()
+}
+
+/// Which Pokemon the dex reply ranks a Pokemon's stats against.
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum RankAmong {
+    #[name = "All Pokemon"]
+    All,
+    #[name = "Fully evolved Pokemon"]
+    FullyEvolved,
+}
+
+/// Width of the stat bars, in characters.
+const BAR_WIDTH: usize = 12;
+/// Base stat filling a whole bar. The few higher ones are capped so the rest stay readable.
+const BAR_MAX: f64 = 200.0;
+
+/// The dex reply's Stats field: a bar per stat with the percentage of `pool` having a lower one,
+/// where the BST ranks in `pool`, and `note` saying what `pool` is.
+pub fn stat_bars(pokemon: &Pokemon, pool: &[BaseStats], note: &str) -> String {
+    let mut lines = String::from("```c\n");
+    for (i, (stat, value)) in Stat::ALL.iter().zip(pokemon.base_stats()).enumerate() {
+        let lower = pool.iter().filter(|other| other.stats[i] < value).count();
+        lines.push_str(&format!(
+            "{:<4}{:>3} {} {:>3}%\n",
+            stat.short_name(),
+            value,
+            bar(value),
+            (100 * lower).checked_div(pool.len()).unwrap_or(0),
+        ));
+    }
+    let rank = 1 + pool.iter().filter(|other| other.total() > pokemon.base_total).count();
+    lines.push_str(&format!("BST {:>3} #{}/{}\n```{}", pokemon.base_total, rank, pool.len(), note));
+    lines
+}
+
+/// A bar of `BAR_WIDTH` characters, in eighths of a character.
+fn bar(value: u8) -> String {
+    let eighths = ((value as f64 / BAR_MAX).min(1.0) * (BAR_WIDTH * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if let Some(partial) = [None, Some('▏'), Some('▎'), Some('▍'), Some('▌'), Some('▋'), Some('▊'), Some('▉')][eighths % 8] {
+        bar.push(partial);
+    }
+    let filled = bar.chars().count();
+    bar.push_str(&"░".repeat(BAR_WIDTH - filled));
+    bar
+}
+
+/// Gen 3+ HP formula.
+pub fn calc_hp(base: u8, iv: u8, ev: u8, level: u8) -> u16 {
+    let (base, iv, ev, level) = (u32::from(base), u32::from(iv), u32::from(ev), u32::from(level));
+    ((2 * base + iv + ev / 4) * level / 100 + level + 10) as u16
+}
+
+/// Gen 3+ formula for every stat besides HP. `nature_multiplier` is a percentage.
+pub fn calc_stat(base: u8, iv: u8, ev: u8, level: u8, nature_multiplier: u16) -> u16 {
+    let (base, iv, ev, level) = (u32::from(base), u32::from(iv), u32::from(ev), u32::from(level));
+    ((((2 * base + iv + ev / 4) * level / 100) + 5) * u32::from(nature_multiplier) / 100) as u16
+}
+
+/// Shedinja, whose HP is always 1. Matched on the internal name, which datasets do not translate or rename.
+fn has_fixed_hp(pokemon: &Pokemon) -> bool {
+    pokemon.internal_name.as_deref() == Some("SPECIES_SHEDINJA")
+}
+
+/// Final stats for a set of base stats. Shedinja's HP is always 1.
+pub fn calc_stats(pokemon: &Pokemon, spread: &Spread) -> [u16; 6] {
+    let base = pokemon.base_stats();
+    let mut stats = [0u16; 6];
+    for (i, stat) in Stat::ALL.iter().enumerate() {
+        stats[i] = match stat {
+            Stat::Hp if has_fixed_hp(pokemon) => 1,
+            Stat::Hp => calc_hp(base[i], spread.ivs[i], spread.evs[i], spread.level),
+            _ => calc_stat(
+                base[i],
+                spread.ivs[i],
+                spread.evs[i],
+                spread.level,
+                spread.nature.multiplier(*stat),
+            ),
+        };
+    }
+    stats
+}
+
+/// Lowest (0 IVs, 0 EVs, hindering nature) and highest (31 IVs, 252 EVs, beneficial nature)
+/// value of every stat at the given level.
+pub fn calc_ranges(pokemon: &Pokemon, level: u8) -> [(u16, u16); 6] {
+    let base = pokemon.base_stats();
+    let mut ranges = [(0u16, 0u16); 6];
+    for (i, stat) in Stat::ALL.iter().enumerate() {
+        ranges[i] = match stat {
+            Stat::Hp if has_fixed_hp(pokemon) => (1, 1),
+            Stat::Hp => (
+                calc_hp(base[i], 0, 0, level),
+                calc_hp(base[i], MAX_IV, MAX_EV, level),
+            ),
+            _ => (
+                calc_stat(base[i], 0, 0, level, 90),
+                calc_stat(base[i], MAX_IV, MAX_EV, level, 110),
+            ),
+        };
+    }
+    ranges
+}
+
+/// Parses IVs or EVs, either as six slash separated values (`31/31/31/31/31/31`) or
+/// Showdown style (`252 Atk / 4 HP / 252 Spe`), where unlisted stats use `default`.
+pub fn parse_spread(input: &str, default: u8, max: u8) -> Result<[u8; 6], String> {
+    let parts: Vec<&str> = input.split('/').map(|part| part.trim()).collect();
+    let mut values = [default; 6];
+    if parts.len() == 6 && parts.iter().all(|part| part.parse::<u8>().is_ok()) {
+        for (value, part) in values.iter_mut().zip(parts) {
+            *value = part.parse::<u8>().unwrap();
+        }
+    } else {
+        for part in parts {
+            let (amount, stat) = part
+                .split_once(' ')
+                .ok_or_else(|| format!("Could not read `{}`, expected something like `252 Atk`.", part))?;
+            let amount = amount
+                .parse::<u8>()
+                .map_err(|_| format!("`{}` is not a valid number.", amount))?;
+            let index = Stat::from_key(stat.trim())
+                .and_then(|stat| stat.index())
+                .ok_or_else(|| format!("`{}` is not a stat.", stat.trim()))?;
+            values[index] = amount;
+        }
+    }
+    if let Some(value) = values.iter().find(|value| **value > max) {
+        return Err(format!("{} is above the maximum of {}.", value, max));
+    }
+    Ok(values)
+}
+
+/// Calculate a Pokemon's stats for a given level, IVs, EVs and nature.
+///
+/// Without a spread, shows the lowest and highest possible stats at level 50 and 100.
+///
+/// Usage:
+/// /stats Blaziken
+/// /stats Blaziken level:50 evs:252 Atk / 4 HP / 252 Spe nature:Adamant
+#[poise::command(prefix_command, slash_command)]
+pub async fn stats(
+    ctx: Context<'_>,
+    #[description = "Pokemon to calculate the stats of."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    pokemon: String,
+    #[description = "Level (defaults to 100)."]
+    #[min = 1]
+    #[max = 100]
+    level: Option<u8>,
+    #[description = "IVs, e.g. 31/31/31/31/31/31 or 0 Atk (unlisted IVs are 31)."]
+    ivs: Option<String>,
+    #[description = "EVs, e.g. 252 Atk / 4 HP / 252 Spe (unlisted EVs are 0)."]
+    evs: Option<String>,
+    #[description = "Nature (defaults to a neutral nature)."]
+    nature: Option<Nature>,
+    #[description = "Game dataset to use."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return super::reply_error(ctx, message).await,
+    };
+    let Some(p) = super::resolve_pokemon(&dataset, &pokemon)? else {
+        return super::reply_error(ctx, super::unknown_pokemon(&pokemon)).await;
+    };
+    crate::analytics::target(ctx, &dataset, p.id).await;
+    let embed = if level.is_none() && ivs.is_none() && evs.is_none() && nature.is_none() {
+        ranges_embed(&p)
+    } else {
+        let spread = match Spread::from_options(level, ivs.as_deref(), evs.as_deref(), nature) {
+            Ok(spread) => spread,
+            Err(message) => return super::reply_error(ctx, message).await,
+        };
+        spread_embed(&p, &spread)
+    };
+    ctx.send(poise::CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
+
+fn ranges_embed(p: &Pokemon) -> serenity::CreateEmbed {
+    let base = p.base_stats();
+    let (level_50, level_100) = (calc_ranges(p, 50), calc_ranges(p, 100));
+    let mut table = "```c\n     Base  Lv.50    Lv.100\n".to_owned();
+    for (i, stat) in Stat::ALL.iter().enumerate() {
+        let range_50 = format!("{}-{}", level_50[i].0, level_50[i].1);
+        let range_100 = format!("{}-{}", level_100[i].0, level_100[i].1);
+        table.push_str(&format!(
+            "{0:<4} {1:<5} {2:<8} {3}\n",
+            stat.short_name().to_owned() + ":",
+            base[i],
+            range_50,
+            range_100,
+        ));
+    }
+    table.push_str("```");
+    serenity::CreateEmbed::new()
+        .title(format!("#{0}: {1}", p.pokedex_id.unwrap_or(0), p.name))
+        .colour(super::get_color_from_type(p.type1_name.as_deref().unwrap_or("")))
+        .field("Stat Ranges", table, false)
+        .footer(serenity::CreateEmbedFooter::new(
+            "Lowest: 0 IVs, 0 EVs, hindering nature. Highest: 31 IVs, 252 EVs, beneficial nature.",
+        ))
+}
+
+fn spread_embed(p: &Pokemon, spread: &Spread) -> serenity::CreateEmbed {
+    let base = p.base_stats();
+    let stats = calc_stats(p, spread);
+    let mut table = "```c\n     Base IV EV  Stat\n".to_owned();
+    for (i, stat) in Stat::ALL.iter().enumerate() {
+        let nature_sign = match spread.nature.multiplier(*stat) {
+            110 => "+",
+            90 => "-",
+            _ => "",
+        };
+        table.push_str(&format!(
+            "{0:<4} {1:<4} {2:<2} {3:<3} {4}{5}\n",
+            stat.short_name().to_owned() + ":",
+            base[i],
+            spread.ivs[i],
+            spread.evs[i],
+            stats[i],
+            nature_sign
+        ));
+    }
+    table.push_str("```");
+    serenity::CreateEmbed::new()
+        .title(format!("#{0}: {1}", p.pokedex_id.unwrap_or(0), p.name))
+        .colour(super::get_color_from_type(p.type1_name.as_deref().unwrap_or("")))
+        .field(
+            format!("Level {0} {1}", spread.level, poise::ChoiceParameter::name(&spread.nature)),
+            table,
+            false,
+        )
+}
+
+pub async fn stats_component(
+    ctx: serenity::Context,
+    i: &serenity::ComponentInteraction,
+    dataset: &Dataset,
+    pokemon_id: u16,
+) -> Result<(), Error> {
+    let p = super::get_pokemon_by_id(dataset, &pokemon_id)?;
+    i.create_response(
+        ctx,
+        serenity::CreateInteractionResponse::Message(
+            serenity::CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .embed(ranges_embed(&p)),
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pokemon(internal_name: &str, base_stats: [u8; 6]) -> Pokemon {
+        let [base_hp, base_atk, base_def, base_spa, base_spd, base_spe] = base_stats;
+        Pokemon {
+            internal_name: Some(internal_name.to_owned()),
+            base_hp,
+            base_atk,
+            base_def,
+            base_spa,
+            base_spd,
+            base_spe,
+            ..Pokemon::default()
+        }
+    }
+
+    #[test]
+    fn matches_reference_stats() {
+        // Garchomp is 108/130/95/80/85/102.
+        assert_eq!(calc_stat(102, 31, 252, 100, Nature::Jolly.multiplier(Stat::Spe)), 333);
+        assert_eq!(calc_stat(102, 31, 252, 100, 100), 303);
+        assert_eq!(calc_stat(80, 31, 0, 100, Nature::Jolly.multiplier(Stat::Spa)), 176);
+        assert_eq!(calc_hp(108, 31, 252, 50), 215);
+        assert_eq!(calc_hp(108, 31, 0, 50), 183);
+        assert_eq!(calc_hp(108, 31, 252, 100), 420);
+
+        let garchomp = pokemon("SPECIES_GARCHOMP", [108, 130, 95, 80, 85, 102]);
+        let spread = Spread {
+            level: 100,
+            ivs: [31; 6],
+            evs: [4, 252, 0, 0, 0, 252],
+            nature: Nature::Jolly,
+        };
+        assert_eq!(calc_stats(&garchomp, &spread), [358, 359, 226, 176, 206, 333]);
+    }
+
+    #[test]
+    fn shedinja_always_has_1_hp() {
+        let shedinja = pokemon("SPECIES_SHEDINJA", [1, 90, 45, 30, 30, 40]);
+        let spread = Spread::from_options(Some(50), None, Some("252 HP"), None).unwrap();
+        assert_eq!(calc_stats(&shedinja, &spread)[0], 1);
+        assert_eq!(calc_ranges(&shedinja, 100)[0], (1, 1));
+        // Only the species, not whatever a dataset calls it.
+        let renamed = Pokemon {
+            name: "Shedinja".to_owned(),
+            ..pokemon("SPECIES_NINJASK", [61, 90, 45, 50, 50, 160])
+        };
+        assert_eq!(calc_ranges(&renamed, 100)[0], (232, 326));
+    }
+
+    #[test]
+    fn natures_raise_and_lower_stats() {
+        assert_eq!(Nature::Adamant.modifiers(), Some((Stat::Atk, Stat::Spa)));
+        assert_eq!(Nature::Jolly.modifiers(), Some((Stat::Spe, Stat::Spa)));
+        assert_eq!(Nature::Modest.modifiers(), Some((Stat::Spa, Stat::Atk)));
+        assert_eq!(Nature::Hardy.modifiers(), None);
+        assert_eq!(Nature::Quirky.modifiers(), None);
+        assert_eq!(Nature::Adamant.multiplier(Stat::Atk), 110);
+        assert_eq!(Nature::Adamant.multiplier(Stat::Spa), 90);
+        assert_eq!(Nature::Adamant.multiplier(Stat::Hp), 100);
+    }
+
+    #[test]
+    fn parses_spreads() {
+        assert_eq!(parse_spread("252 Atk / 4 HP / 252 Spe", 0, MAX_EV).unwrap(), [4, 252, 0, 0, 0, 252]);
+        assert_eq!(parse_spread("31/0/31/31/31/0", MAX_IV, MAX_IV).unwrap(), [31, 0, 31, 31, 31, 0]);
+        assert_eq!(parse_spread("0 Atk", MAX_IV, MAX_IV).unwrap(), [31, 0, 31, 31, 31, 31]);
+    }
+
+    #[test]
+    fn rejects_malformed_spreads() {
+        assert_eq!(
+            parse_spread("252Atk", 0, MAX_EV).unwrap_err(),
+            "Could not read `252Atk`, expected something like `252 Atk`."
+        );
+        assert_eq!(parse_spread("lots Atk", 0, MAX_EV).unwrap_err(), "`lots` is not a valid number.");
+        assert_eq!(parse_spread("300 Atk", 0, MAX_EV).unwrap_err(), "`300` is not a valid number.");
+        assert_eq!(parse_spread("252 Luck", 0, MAX_EV).unwrap_err(), "`Luck` is not a stat.");
+        assert_eq!(parse_spread("32/31/31/31/31/31", MAX_IV, MAX_IV).unwrap_err(), "32 is above the maximum of 31.");
+        assert_eq!(parse_spread("31/31/31", MAX_IV, MAX_IV).unwrap_err(), "Could not read `31`, expected something like `252 Atk`.");
+        assert_eq!(
+            Spread::from_options(None, None, Some("252 Atk / 252 Spe / 252 HP"), None).unwrap_err(),
+            "756 EVs is above the maximum of 510."
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::datasets::Dataset;
 use crate::Error;
 use poise::serenity_prelude as serenity;
 
@@ -8,11 +9,12 @@ pub struct Effectiveness {
 }
 
 pub fn get_effectiveness(
+    dataset: &Dataset,
     pokemon_id: &u16,
 ) -> Result<Vec<Effectiveness>, Error> {
-    let sql = std::fs::read_to_string("./src/dex/queries/type_effectiveness.sql")
-        .unwrap();
-    let conn = rusqlite::Connection::open("rowedex.db").unwrap();
+    let sql = crate::dex::read_query("./src/dex/queries/type_effectiveness.sql");
+    let _timer = crate::metrics::time_query("./src/dex/queries/type_effectiveness.sql");
+    let conn = dataset.open();
     let mut stmt = conn.prepare(&sql).unwrap();
     let rows = stmt.query_map([pokemon_id], |row| {
         Ok(Effectiveness {
@@ -30,11 +32,12 @@ pub fn get_effectiveness(
 pub async fn type_effectiveness_component(
     ctx: serenity::Context,
     i: &serenity::ComponentInteraction,
+    dataset: &Dataset,
     pokemon_id: u16,
 ) -> Result<(), Error> {
     const LONGEST_NAME_LEN: usize = 9;
-    let type_effectiveness = get_effectiveness(&pokemon_id);
-    let mut defensive: String = "```c\n".to_string();
+    let type_effectiveness = get_effectiveness(dataset, &pokemon_id);
+    let mut defensive: String = "```c\n".to_owned();
     let mut offensive: String = defensive.clone();
     type_effectiveness.iter().for_each(|results| {
         results.iter().for_each(|result| {
@@ -0,0 +1,94 @@
+//! Offline maintenance of rowedex.db and the sprite store, run as `rowedex dexctl <command>` instead of starting the bot.
+
+pub mod check;
+pub mod decomp;
+pub mod import;
+pub mod sprites;
+
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage:
+  dexctl import <decomp dir> [--db rowedex.db] [--out rowedex.import.db]
+      Rebuilds every table from a local checkout of the R.O.W.E decomp into a copy
+      of the database, and reports what changed compared to the current one.
+  dexctl check [--db rowedex.db]
+      Validates the database and prints a JSON report. Exits with 1 if any check fails.
+  dexctl sprites <decomp dir> [--dataset rowe] [--out sprites]
+      Imports the front, back, shiny and icon sprites of every species into the sprite
+      store, so they are served without depending on the remote repository.";
+
+/// Runs a dexctl command and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let result = match args.first().map(String::as_str) {
+        Some("import") => {
+            let Some(decomp_dir) = args.get(1).filter(|arg| !arg.starts_with("--")) else {
+                println!("{}", USAGE);
+                return 2;
+            };
+            let (db, out) = match (option(args, "--db"), option(args, "--out")) {
+                (Ok(db), Ok(out)) => (
+                    db.unwrap_or_else(|| PathBuf::from("rowedex.db")),
+                    out.unwrap_or_else(|| PathBuf::from("rowedex.import.db")),
+                ),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("{}\n\n{}", e, USAGE);
+                    return 2;
+                }
+            };
+            if db == out {
+                println!("--out must be different from --db, the current database is needed for the report.");
+                return 2;
+            }
+            import::run(&PathBuf::from(decomp_dir), &db, &out).map(|()| 0)
+        }
+        Some("check") => {
+            let db = match option(args, "--db") {
+                Ok(db) => db.unwrap_or_else(|| PathBuf::from("rowedex.db")),
+                Err(e) => {
+                    println!("{}\n\n{}", e, USAGE);
+                    return 2;
+                }
+            };
+            check::run(&db).map(|passed| if passed { 0 } else { 1 })
+        }
+        Some("sprites") => {
+            let Some(decomp_dir) = args.get(1).filter(|arg| !arg.starts_with("--")) else {
+                println!("{}", USAGE);
+                return 2;
+            };
+            let (dataset, out) = match (option(args, "--dataset"), option(args, "--out")) {
+                (Ok(dataset), Ok(out)) => (
+                    dataset.map_or("rowe".to_owned(), |dataset| dataset.to_string_lossy().into_owned()),
+                    out.unwrap_or_else(|| crate::config::get().sprites.dir.clone()),
+                ),
+                (Err(e), _) | (_, Err(e)) => {
+                    println!("{}\n\n{}", e, USAGE);
+                    return 2;
+                }
+            };
+            sprites::run(&PathBuf::from(decomp_dir), &out.join(dataset)).map(|()| 0)
+        }
+        _ => {
+            println!("{}", USAGE);
+            return 2;
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+/// Value of a `--name value` option.
+fn option(args: &[String], name: &str) -> Result<Option<PathBuf>, String> {
+    match args.iter().position(|arg| arg == name) {
+        Some(i) => args
+            .get(i + 1)
+            .map(|value| Some(PathBuf::from(value)))
+            .ok_or_else(|| format!("{} needs a value.", name)),
+        None => Ok(None),
+    }
+}
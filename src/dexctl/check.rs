@@ -0,0 +1,177 @@
+use std::path::Path;
+use rusqlite::{Connection, OpenFlags};
+use crate::Error;
+
+/// (name, query) of every integrity check. Each query returns one (subject, message) row per problem.
+const CHECKS: [(&str, &str); 5] = [
+    ("foreign_keys", "./src/dexctl/queries/check_foreign_keys.sql"),
+    ("sighting_chances", "./src/dexctl/queries/check_sighting_chances.sql"),
+    ("base_types", "./src/dexctl/queries/check_base_types.sql"),
+    ("abilities", "./src/dexctl/queries/check_abilities.sql"),
+    ("sprites", "./src/dexctl/queries/check_sprites.sql"),
+];
+
+/// Problems in the shipped rowedex.db that no decomp checkout at hand can fix, as (check, subject, why).
+/// They are still reported, with the reason, but do not fail the check. Remove an entry once its data is fixed.
+const KNOWN_FAILURES: [(&str, &str, &str); 11] = [
+    ("sighting_chances", "location Petalburg Woods (Hidden)", "Two encounter tables are stored under one location."),
+    ("sighting_chances", "location Shoal Cave Low Tide Entrance Room (Hidden)", "Two encounter tables are stored under one location."),
+    ("sighting_chances", "location Altering Cave (Day)", "Its nine alternate encounter tables are stored under one location."),
+    ("sighting_chances", "location Scorched Slab (Surfing)", "Shipped without its 1% slot."),
+    ("sighting_chances", "location Scorched Slab B 1f (Surfing)", "Shipped without its 1% slot."),
+    ("abilities", "pokemon SPECIES_KLEAVOR", "Shipped without abilities, to be re-imported from the decomp."),
+    ("abilities", "pokemon SPECIES_SNEASLER", "Shipped without abilities, to be re-imported from the decomp."),
+    ("abilities", "pokemon SPECIES_ENAMORUS", "Shipped without abilities, to be re-imported from the decomp."),
+    ("sprites", "pokemon SPECIES_KLEAVOR", "Shipped without a sprite, to be re-imported from the decomp."),
+    ("sprites", "pokemon SPECIES_SNEASLER", "Shipped without a sprite, to be re-imported from the decomp."),
+    ("sprites", "pokemon SPECIES_ENAMORUS", "Shipped without a sprite, to be re-imported from the decomp."),
+];
+
+pub struct Failure {
+    pub subject: String,
+    pub message: String,
+    /// Why the failure is expected, if it is one of `KNOWN_FAILURES`.
+    pub known: Option<&'static str>,
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub failures: Vec<Failure>,
+}
+
+impl Check {
+    pub fn passed(&self) -> bool {
+        self.failures.iter().all(|failure| failure.known.is_some())
+    }
+}
+
+pub struct Report {
+    pub schema_version: u32,
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(Check::passed)
+    }
+
+    /// Failures that are not known ones.
+    pub fn failure_count(&self) -> usize {
+        self.checks.iter().flat_map(|check| &check.failures).filter(|failure| failure.known.is_none()).count()
+    }
+
+    pub fn to_json(&self, database: &Path) -> serde_json::Value {
+        serde_json::json!({
+            "database": database.display().to_string(),
+            "schema_version": self.schema_version,
+            "passed": self.passed(),
+            "failure_count": self.failure_count(),
+            "checks": self.checks.iter().map(|check| serde_json::json!({
+                "name": check.name,
+                "passed": check.passed(),
+                "failures": check.failures.iter().map(|failure| serde_json::json!({
+                    "subject": failure.subject,
+                    "message": failure.message,
+                    "known": failure.known,
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Runs every integrity check against an open database.
+pub fn check(conn: &Connection) -> Result<Report, rusqlite::Error> {
+    let mut checks: Vec<Check> = Vec::new();
+    for (name, path) in CHECKS {
+        let sql = crate::dex::read_query(path);
+        let _timer = crate::metrics::time_query(path);
+        let mut stmt = conn.prepare(&sql)?;
+        let failures = stmt
+            .query_map([], |row| {
+                let subject: String = row.get(0)?;
+                let known = KNOWN_FAILURES
+                    .iter()
+                    .find(|(check, known_subject, _)| *check == name && *known_subject == subject)
+                    .map(|(_, _, why)| *why);
+                Ok(Failure {
+                    subject,
+                    message: row.get(1)?,
+                    known,
+                })
+            })?
+            .collect::<Result<Vec<Failure>, _>>()?;
+        checks.push(Check { name, failures });
+    }
+    Ok(Report {
+        schema_version: crate::migrations::current_version(conn)?,
+        checks,
+    })
+}
+
+/// Opens the database read-only, so a mistyped path is an error instead of a new empty database.
+pub fn open(db: &Path) -> Result<Connection, rusqlite::Error> {
+    Connection::open_with_flags(db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+}
+
+/// Prints the JSON report for `db` and returns whether every check passed.
+pub fn run(db: &Path) -> Result<bool, Error> {
+    let report = check(&open(db)?)?;
+    println!("{}", serde_json::to_string_pretty(&report.to_json(db))?);
+    Ok(report.passed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failures(report: &Report) -> Vec<String> {
+        report
+            .checks
+            .iter()
+            .flat_map(|check| {
+                check
+                    .failures
+                    .iter()
+                    .filter(|failure| failure.known.is_none())
+                    .map(move |failure| format!("{}: {} {}", check.name, failure.subject, failure.message))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shipped_database_passes() {
+        let report = check(&open(Path::new("rowedex.db")).unwrap()).unwrap();
+        assert_eq!(failures(&report), Vec::<String>::new());
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn known_failures_still_occur() {
+        let report = check(&open(Path::new("rowedex.db")).unwrap()).unwrap();
+        for (name, subject, _) in KNOWN_FAILURES {
+            let check = report.checks.iter().find(|check| check.name == name).unwrap();
+            assert!(
+                check.failures.iter().any(|failure| failure.subject == subject),
+                "{} no longer fails {}, remove it from KNOWN_FAILURES",
+                subject,
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn broken_copy_fails() {
+        let copy = std::env::temp_dir().join(format!("rowedex-check-{}.db", std::process::id()));
+        std::fs::copy("rowedex.db", &copy).unwrap();
+        let conn = Connection::open(&copy).unwrap();
+        conn.execute(
+            "delete from pokemon_ability_relationships where pokemon = (select id from pokemon where internal_name = 'SPECIES_BULBASAUR')",
+            [],
+        )
+        .unwrap();
+        let report = check(&conn).unwrap();
+        drop(conn);
+        std::fs::remove_file(&copy).unwrap();
+        assert_eq!(failures(&report), ["abilities: pokemon SPECIES_BULBASAUR has no abilities"]);
+    }
+}
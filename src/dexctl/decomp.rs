@@ -0,0 +1,876 @@
+//! Reads Pokemon data out of a local checkout of the R.O.W.E decomp.
+//!
+//! The headers are parsed just enough to pull out designated initializers
+//! (`[SPECIES_BULBASAUR] = { .baseHP = 45, ... }`), array definitions and macro calls.
+//! Both the older (base_stats.h, tmhm/tutor learnsets) and newer (species_info.h) layouts are understood.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use crate::Error;
+
+pub struct Species {
+    pub internal_name: String,
+    pub name: Option<String>,
+    /// HP, Atk, Def, SpA, SpD, Spe
+    pub base_stats: [u8; 6],
+    pub types: Vec<String>,
+    pub egg_groups: Vec<String>,
+    pub items: Vec<String>,
+    pub abilities: Vec<String>,
+}
+
+pub struct Move {
+    pub internal_name: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub effect: String,
+    pub power: u8,
+    pub type_name: String,
+    pub accuracy: u8,
+    pub pp: u8,
+    pub chance: u8,
+    pub target: String,
+    pub priority: i8,
+    pub split: String,
+    pub flags: Vec<String>,
+}
+
+pub struct Ability {
+    pub internal_name: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+pub struct Evolution {
+    pub species: String,
+    pub method: String,
+    pub param: String,
+    pub evolved: String,
+}
+
+pub struct Sighting {
+    pub map: String,
+    pub encounter_method: String,
+    pub species: String,
+    pub chance: u8,
+}
+
+/// Everything read from the decomp, keyed by internal names (SPECIES_*, MOVE_*, ...).
+pub struct Decomp {
+    pub species: Vec<Species>,
+    pub moves: Vec<Move>,
+    pub abilities: Vec<Ability>,
+    pub levelup_learnsets: BTreeMap<String, Vec<(u8, String)>>,
+    pub tmhm_learnsets: BTreeMap<String, Vec<String>>,
+    pub tutor_learnsets: BTreeMap<String, Vec<String>>,
+    pub egg_learnsets: BTreeMap<String, Vec<String>>,
+    pub evolutions: Vec<Evolution>,
+    pub sightings: Vec<Sighting>,
+    /// Things that were skipped, to be shown in the import report.
+    pub warnings: Vec<String>,
+}
+
+pub fn load(root: &Path) -> Result<Decomp, Error> {
+    let mut warnings: Vec<String> = Vec::new();
+    let species = load_species(root)?;
+    let moves = load_moves(root)?;
+    let abilities = load_abilities(root, &mut warnings);
+    let levelup_learnsets = load_levelup_learnsets(root)?;
+    let tmhm_learnsets = load_move_tables(root, "src/data/pokemon/tmhm_learnsets.h", &mut warnings);
+    let tutor_learnsets = load_move_tables(root, "src/data/pokemon/tutor_learnsets.h", &mut warnings);
+    let egg_learnsets = load_egg_learnsets(root)?;
+    let evolutions = load_evolutions(root)?;
+    let sightings = load_sightings(root, &mut warnings)?;
+    Ok(Decomp {
+        species,
+        moves,
+        abilities,
+        levelup_learnsets,
+        tmhm_learnsets,
+        tutor_learnsets,
+        egg_learnsets,
+        evolutions,
+        sightings,
+        warnings,
+    })
+}
+
+fn read(root: &Path, relative: &str) -> Result<String, Error> {
+    let path = root.join(relative);
+    std::fs::read_to_string(&path)
+        .map(|contents| strip_comments(&contents))
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e).into())
+}
+
+/// Reads the first of several possible locations of a file.
+fn read_any(root: &Path, candidates: &[&str]) -> Result<String, Error> {
+    candidates
+        .iter()
+        .find(|relative| root.join(relative).exists())
+        .map(|relative| read(root, relative))
+        .unwrap_or_else(|| Err(format!("Could not find any of {}", candidates.join(", ")).into()))
+}
+
+fn load_species(root: &Path) -> Result<Vec<Species>, Error> {
+    let info = read_any(
+        root,
+        &["src/data/pokemon/species_info.h", "src/data/pokemon/base_stats.h"],
+    )?;
+    let names: HashMap<String, String> = read(root, "src/data/text/species_names.h")
+        .map(|names| {
+            designated_entries(&names)
+                .into_iter()
+                .filter_map(|(key, value)| string_literal(&value).map(|name| (key, name)))
+                .collect()
+        })
+        .unwrap_or_default();
+    parse_species(&info, &names)
+}
+
+/// Species entries of a species_info.h or base_stats.h, named from `names` when the entry has no speciesName.
+fn parse_species(info: &str, names: &HashMap<String, String>) -> Result<Vec<Species>, Error> {
+    let mut species: Vec<Species> = Vec::new();
+    for (key, body) in designated_entries(info) {
+        if !key.starts_with("SPECIES_") || key == "SPECIES_NONE" {
+            continue;
+        }
+        let fields = fields(&body);
+        let stat = |name: &str| -> Result<u8, Error> {
+            let value = fields
+                .get(name)
+                .ok_or_else(|| format!("{}: .{} is missing", key, name))?;
+            number(value).ok_or_else(|| format!("{}: .{} = {} is not a number", key, name, value).into())
+        };
+        // Newer layouts use arrays, older ones numbered fields.
+        let pair = |array: &str, first: &str, second: &str| -> Vec<String> {
+            match fields.get(array) {
+                Some(value) => list(value),
+                None => [first, second]
+                    .iter()
+                    .filter_map(|name| fields.get(*name).cloned())
+                    .collect(),
+            }
+        };
+        let mut abilities = fields.get("abilities").map(|value| list(value)).unwrap_or_default();
+        if let Some(hidden) = fields.get("abilityHidden") {
+            abilities.extend(list(hidden));
+        }
+        abilities.retain(|ability| ability != "ABILITY_NONE");
+        abilities.dedup();
+        species.push(Species {
+            name: fields
+                .get("speciesName")
+                .and_then(|value| string_literal(value))
+                .or_else(|| names.get(&key).cloned()),
+            base_stats: [
+                stat("baseHP")?,
+                stat("baseAttack")?,
+                stat("baseDefense")?,
+                stat("baseSpAttack")?,
+                stat("baseSpDefense")?,
+                stat("baseSpeed")?,
+            ],
+            types: pair("types", "type1", "type2"),
+            egg_groups: pair("eggGroups", "eggGroup1", "eggGroup2"),
+            items: ["itemCommon", "itemRare", "item1", "item2"]
+                .iter()
+                .filter_map(|name| fields.get(*name).cloned())
+                .collect(),
+            abilities,
+            internal_name: key,
+        });
+    }
+    Ok(species)
+}
+
+fn load_moves(root: &Path) -> Result<Vec<Move>, Error> {
+    let battle_moves = read_any(
+        root,
+        &["src/data/battle_moves.h", "src/data/moves_info.h"],
+    )?;
+    let names: HashMap<String, String> = read(root, "src/data/text/move_names.h")
+        .map(|names| {
+            designated_entries(&names)
+                .into_iter()
+                .filter_map(|(key, value)| string_literal(&value).map(|name| (key, name)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let descriptions = read(root, "src/data/text/move_descriptions.h")
+        .map(|descriptions| pointed_strings(&descriptions))
+        .unwrap_or_default();
+
+    let mut moves: Vec<Move> = Vec::new();
+    for (key, body) in designated_entries(&battle_moves) {
+        if !key.starts_with("MOVE_") || key == "MOVE_NONE" {
+            continue;
+        }
+        let fields = fields(&body);
+        let text = |name: &str, default: &str| -> String {
+            fields.get(name).cloned().unwrap_or_else(|| default.to_owned())
+        };
+        // Fields that are left out are 0, but one that is there has to be understood.
+        let field = |name: &str| -> Result<i32, Error> {
+            match fields.get(name) {
+                Some(value) => number(value).ok_or_else(|| format!("{}: .{} = {} is not a number", key, name, value).into()),
+                None => Ok(0),
+            }
+        };
+        moves.push(Move {
+            name: fields
+                .get("name")
+                .and_then(|value| string_literal(value))
+                .or_else(|| names.get(&key).cloned()),
+            description: fields
+                .get("description")
+                .and_then(|value| string_literal(value))
+                .or_else(|| descriptions.get(&key).cloned()),
+            effect: text("effect", "EFFECT_HIT"),
+            power: field("power")? as u8,
+            type_name: text("type", "TYPE_NORMAL"),
+            accuracy: field("accuracy")? as u8,
+            pp: field("pp")? as u8,
+            chance: field("secondaryEffectChance")? as u8,
+            target: text("target", "MOVE_TARGET_SELECTED"),
+            priority: field("priority")? as i8,
+            split: text("split", &text("category", "SPLIT_PHYSICAL")).replace("DAMAGE_CATEGORY_", "SPLIT_"),
+            flags: fields
+                .get("flags")
+                .map(|value| identifiers(value, "FLAG_"))
+                .unwrap_or_default(),
+            internal_name: key,
+        });
+    }
+    Ok(moves)
+}
+
+fn load_abilities(root: &Path, warnings: &mut Vec<String>) -> Vec<Ability> {
+    let Ok(text) = read(root, "src/data/text/abilities.h") else {
+        warnings.push("src/data/text/abilities.h not found, abilities were left as they are.".to_owned());
+        return vec![];
+    };
+    let descriptions = pointed_strings(&text);
+    let mut abilities: Vec<Ability> = Vec::new();
+    for (key, value) in designated_entries(&text) {
+        if !key.starts_with("ABILITY_") || key == "ABILITY_NONE" {
+            continue;
+        }
+        // gAbilityNames holds literals, gAbilityDescriptionPointers identifiers.
+        if let Some(name) = string_literal(&value) {
+            abilities.push(Ability {
+                description: descriptions.get(&key).cloned(),
+                name: Some(name),
+                internal_name: key,
+            });
+        }
+    }
+    abilities
+}
+
+fn load_levelup_learnsets(root: &Path) -> Result<BTreeMap<String, Vec<(u8, String)>>, Error> {
+    let learnsets = read(root, "src/data/pokemon/level_up_learnsets.h")?;
+    let arrays = array_definitions(&learnsets);
+    let pointers = read_any(
+        root,
+        &[
+            "src/data/pokemon/level_up_learnset_pointers.h",
+            "src/data/pokemon/species_info.h",
+        ],
+    )?;
+    let mut sets: BTreeMap<String, Vec<(u8, String)>> = BTreeMap::new();
+    for (key, value) in designated_entries(&pointers) {
+        // species_info.h points at the learnset through a field instead.
+        let array_name = fields(&value)
+            .get("levelUpLearnset")
+            .cloned()
+            .unwrap_or(value);
+        let Some(body) = arrays.get(array_name.trim()) else {
+            continue;
+        };
+        let moves = macro_calls(body, "LEVEL_UP_MOVE")
+            .into_iter()
+            .filter_map(|args| match args.as_slice() {
+                [level, move_name] => Some((level.parse::<u8>().ok()?, move_name.clone())),
+                _ => None,
+            })
+            .collect();
+        sets.insert(key, moves);
+    }
+    Ok(sets)
+}
+
+/// TM/HM and tutor tables, either as bitfield macros or move lists per species.
+fn load_move_tables(root: &Path, relative: &str, warnings: &mut Vec<String>) -> BTreeMap<String, Vec<String>> {
+    let Ok(table) = read(root, relative) else {
+        warnings.push(format!("{} not found, those learnsets will be empty.", relative));
+        return BTreeMap::new();
+    };
+    let arrays = array_definitions(&table);
+    let mut sets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (key, value) in designated_entries(&table) {
+        if !key.starts_with("SPECIES_") {
+            continue;
+        }
+        let body = arrays.get(value.trim()).cloned().unwrap_or(value);
+        let mut moves = identifiers(&body, "MOVE_");
+        // TMHM(TM06_TOXIC) names the move after the TM number.
+        for machine in identifiers(&body, "TM").into_iter().chain(identifiers(&body, "HM")) {
+            if let Some((_, move_name)) = machine.split_once('_') {
+                if machine[2..].split('_').next().is_some_and(|n| n.parse::<u16>().is_ok()) {
+                    moves.push(format!("MOVE_{}", move_name));
+                }
+            }
+        }
+        moves.retain(|m| m != "MOVE_NONE" && m != "MOVE_UNAVAILABLE");
+        moves.sort();
+        moves.dedup();
+        sets.insert(key, moves);
+    }
+    sets
+}
+
+fn load_egg_learnsets(root: &Path) -> Result<BTreeMap<String, Vec<String>>, Error> {
+    let egg_moves = read(root, "src/data/pokemon/egg_moves.h")?;
+    let mut sets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for args in macro_calls(&egg_moves, "egg_moves") {
+        if let Some((species, moves)) = args.split_first() {
+            sets.entry(format!("SPECIES_{}", species))
+                .or_default()
+                .extend(moves.iter().cloned());
+        }
+    }
+    Ok(sets)
+}
+
+fn load_evolutions(root: &Path) -> Result<Vec<Evolution>, Error> {
+    let evolution = read(root, "src/data/pokemon/evolution.h")?;
+    let mut evolutions: Vec<Evolution> = Vec::new();
+    for (key, value) in designated_entries(&evolution) {
+        for group in brace_groups(&value) {
+            let parts = split_top_level(&group, ',');
+            if let [method, param, evolved, ..] = parts.as_slice() {
+                evolutions.push(Evolution {
+                    species: key.clone(),
+                    method: method.clone(),
+                    param: param.clone(),
+                    evolved: evolved.clone(),
+                });
+            }
+        }
+    }
+    Ok(evolutions)
+}
+
+/// Names of the encounter_methods rows each wild_encounters.json field maps to.
+fn encounter_method(field: &str) -> Option<&'static str> {
+    match field {
+        "land_mons" | "land_mons_day" => Some("Day"),
+        "land_mons_night" => Some("Night"),
+        "hidden_mons" => Some("Hidden"),
+        "water_mons" => Some("Surfing"),
+        "rock_smash_mons" => Some("Rock Smash"),
+        "headbutt_mons" => Some("Headbutt"),
+        _ => None,
+    }
+}
+
+fn load_sightings(root: &Path, warnings: &mut Vec<String>) -> Result<Vec<Sighting>, Error> {
+    let path = root.join("src/data/wild_encounters.json");
+    let json: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?,
+    )?;
+    // Per map, encounter method and species, the summed up chance of every slot.
+    let mut chances: BTreeMap<(String, String, String), u32> = BTreeMap::new();
+    for group in json["wild_encounter_groups"].as_array().into_iter().flatten() {
+        let mut rates: HashMap<&str, Vec<u32>> = HashMap::new();
+        let mut fishing_groups: Vec<(&'static str, Vec<usize>)> = Vec::new();
+        for field in group["fields"].as_array().into_iter().flatten() {
+            let field_type = field["type"].as_str().unwrap_or("");
+            let field_rates = field["encounter_rates"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|rate| rate.as_u64())
+                .map(|rate| rate as u32)
+                .collect();
+            rates.insert(field_type, field_rates);
+            if field_type == "fishing_mons" {
+                for (rod, method) in [("old_rod", "Old Rod"), ("good_rod", "Good Rod"), ("super_rod", "Super Rod")] {
+                    let slots = field["groups"][rod]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|slot| slot.as_u64())
+                        .map(|slot| slot as usize)
+                        .collect();
+                    fishing_groups.push((method, slots));
+                }
+            }
+        }
+        for encounter in group["encounters"].as_array().into_iter().flatten() {
+            let Some(map) = encounter["map"].as_str() else {
+                continue;
+            };
+            let Some(fields) = encounter.as_object() else {
+                continue;
+            };
+            for (field_type, table) in fields {
+                let Some(mons) = table["mons"].as_array() else {
+                    continue;
+                };
+                let field_rates = rates.get(field_type.as_str()).cloned().unwrap_or_default();
+                let slot_methods: Vec<Option<&str>> = if field_type == "fishing_mons" {
+                    (0..mons.len())
+                        .map(|slot| {
+                            fishing_groups
+                                .iter()
+                                .find(|(_, slots)| slots.contains(&slot))
+                                .map(|(method, _)| *method)
+                        })
+                        .collect()
+                } else {
+                    let method = encounter_method(field_type);
+                    if method.is_none() {
+                        warnings.push(format!("Skipped unknown encounter type {} on {}.", field_type, map));
+                    }
+                    vec![method; mons.len()]
+                };
+                for (slot, mon) in mons.iter().enumerate() {
+                    let (Some(species), Some(Some(method))) = (mon["species"].as_str(), slot_methods.get(slot)) else {
+                        continue;
+                    };
+                    *chances
+                        .entry((map.to_owned(), method.to_string(), species.to_owned()))
+                        .or_default() += field_rates.get(slot).copied().unwrap_or(0);
+                }
+            }
+        }
+    }
+    // Fishing rates are per rod, so every rod's slots sum to 100 on their own.
+    Ok(chances
+        .into_iter()
+        .filter(|(_, chance)| *chance > 0)
+        .map(|((map, encounter_method, species), chance)| Sighting {
+            map,
+            encounter_method,
+            species,
+            chance: chance.min(100) as u8,
+        })
+        .collect())
+}
+
+/// Turns a constant such as MAP_GRANITE_CAVE_B1F into the name used by rowedex.db ("Granite Cave B 1f").
+pub fn humanize(constant: &str) -> String {
+    let words = match constant.split_once('_') {
+        Some((prefix, rest)) if prefix.chars().all(|c| c.is_ascii_uppercase()) && !rest.is_empty() => rest,
+        _ => constant,
+    };
+    words
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut output = String::new();
+            let mut previous: Option<char> = None;
+            for (i, c) in word.chars().enumerate() {
+                if c.is_ascii_digit() && previous.is_some_and(|p| p.is_ascii_alphabetic()) {
+                    output.push(' ');
+                }
+                if i == 0 {
+                    output.extend(c.to_uppercase());
+                } else {
+                    output.extend(c.to_lowercase());
+                }
+                previous = Some(c);
+            }
+            output
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_string = !in_string;
+                output.push(c);
+            }
+            '\\' if in_string => {
+                output.push(c);
+                if let Some(escaped) = chars.next() {
+                    output.push(escaped);
+                }
+            }
+            '/' if !in_string && chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if !in_string && chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Index just past the bracket/brace/paren closing the one at `open`, skipping string literals.
+fn matching_close(source: &[u8], open: usize) -> usize {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut i = open;
+    while i < source.len() {
+        match source[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            b'{' | b'(' | b'[' if !in_string => depth += 1,
+            b'}' | b')' | b']' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    source.len()
+}
+
+/// Every `[KEY] = value` pair, where value is either a braced block (without the braces)
+/// or everything up to the next top-level comma.
+fn designated_entries(source: &str) -> Vec<(String, String)> {
+    let bytes = source.as_bytes();
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut i = 0;
+    while let Some(offset) = source[i..].find('[') {
+        let open = i + offset;
+        let close = matching_close(bytes, open);
+        // `MOVE_POUND - 1` style keys are reduced to their identifier.
+        let key = source[open + 1..close.saturating_sub(1)]
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("")
+            .to_owned();
+        let rest = source[close..].trim_start();
+        // Designators follow an opening brace or a comma, unlike array sizes in declarations.
+        let designator = matches!(source[..open].trim_end().chars().last(), Some('{') | Some(','));
+        let is_entry = designator
+            && key.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+            && rest.starts_with('=');
+        if !is_entry {
+            i = open + 1;
+            continue;
+        }
+        let value_start = close + (source[close..].len() - rest.len()) + 1;
+        let value_start = value_start + (source[value_start..].len() - source[value_start..].trim_start().len());
+        let value_end = if bytes.get(value_start) == Some(&b'{') {
+            matching_close(bytes, value_start)
+        } else {
+            value_start + top_level_end(&source[value_start..])
+        };
+        let value = source[value_start..value_end].trim();
+        let value = value
+            .strip_prefix('{')
+            .and_then(|v| v.strip_suffix('}'))
+            .unwrap_or(value);
+        entries.push((key, value.trim().to_owned()));
+        i = value_end;
+    }
+    entries
+}
+
+/// Length of `source` up to the first comma, closing brace or semicolon outside of any nesting.
+fn top_level_end(source: &str) -> usize {
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'(' | b'[' | b'{' => {
+                i = if bytes[i] == b'"' {
+                    i + 1 + source[i + 1..].find('"').map_or(source.len(), |end| end + 1)
+                } else {
+                    matching_close(bytes, i)
+                };
+                continue;
+            }
+            b',' | b'}' | b';' => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    source.len()
+}
+
+fn split_top_level(source: &str, separator: char) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    let mut rest = source;
+    while !rest.trim().is_empty() {
+        let end = if separator == ',' { top_level_end(rest) } else { rest.len() };
+        parts.push(rest[..end].trim().to_owned());
+        rest = rest.get(end + 1..).unwrap_or("");
+    }
+    parts
+}
+
+/// `.name = value` pairs of a struct initializer.
+fn fields(body: &str) -> HashMap<String, String> {
+    split_top_level(body, ',')
+        .into_iter()
+        .filter_map(|part| {
+            let (name, value) = part.strip_prefix('.')?.split_once('=')?;
+            Some((name.trim().to_owned(), value.trim().to_owned()))
+        })
+        .collect()
+}
+
+/// Contents of every top-level `{...}` group.
+fn brace_groups(source: &str) -> Vec<String> {
+    let bytes = source.as_bytes();
+    let mut groups: Vec<String> = Vec::new();
+    let mut i = 0;
+    while let Some(offset) = source[i..].find('{') {
+        let open = i + offset;
+        let close = matching_close(bytes, open);
+        groups.push(source[open + 1..close - 1].trim().to_owned());
+        i = close;
+    }
+    groups
+}
+
+/// A numeric field value such as `45` or `(45)`. Conditionals like
+/// `P_UPDATED_STATS >= GEN_7 ? 100 : 90` resolve to their first branch, the latest generation's value.
+fn number<T: std::str::FromStr>(value: &str) -> Option<T> {
+    let value = match value.split_once('?') {
+        Some((_, branches)) => branches.split_once(':')?.0,
+        None => value,
+    };
+    value
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// `{A, B, C}` or a single identifier as a list.
+fn list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+        .unwrap_or(value);
+    split_top_level(inner, ',')
+}
+
+/// Arguments of every call to the given macro.
+fn macro_calls(source: &str, name: &str) -> Vec<Vec<String>> {
+    let bytes = source.as_bytes();
+    let mut calls: Vec<Vec<String>> = Vec::new();
+    let pattern = format!("{}(", name);
+    let mut i = 0;
+    while let Some(offset) = source[i..].find(&pattern) {
+        let start = i + offset;
+        let preceded_by_identifier = start > 0
+            && (bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_');
+        let open = start + name.len();
+        let close = matching_close(bytes, open);
+        if !preceded_by_identifier {
+            calls.push(split_top_level(&source[open + 1..close - 1], ','));
+        }
+        i = close;
+    }
+    calls
+}
+
+/// `name[] = { ... }` array definitions, by name.
+fn array_definitions(source: &str) -> HashMap<String, String> {
+    let bytes = source.as_bytes();
+    let mut arrays: HashMap<String, String> = HashMap::new();
+    let mut i = 0;
+    while let Some(offset) = source[i..].find("[] =") {
+        let position = i + offset;
+        let name_start = source[..position]
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .map_or(0, |p| p + 1);
+        let name = source[name_start..position].to_owned();
+        let Some(open) = source[position..].find('{').map(|o| position + o) else {
+            break;
+        };
+        let close = matching_close(bytes, open);
+        arrays.insert(name, source[open + 1..close - 1].to_owned());
+        i = close;
+    }
+    arrays
+}
+
+/// Every identifier starting with `prefix`.
+fn identifiers(source: &str, prefix: &str) -> Vec<String> {
+    source
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|word| word.starts_with(prefix) && word.len() > prefix.len())
+        .map(|word| word.to_owned())
+        .collect()
+}
+
+/// Text of a `_("...")` or `COMPOUND_STRING("...")` literal. Adjacent literals are joined
+/// and in-game line breaks become spaces.
+fn string_literal(value: &str) -> Option<String> {
+    let mut output = String::new();
+    let mut found = false;
+    let mut rest = value;
+    while let Some(start) = rest.find('"') {
+        let after = &rest[start + 1..];
+        let mut end = None;
+        let mut escaped = false;
+        for (i, c) in after.char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => {
+                    end = Some(i);
+                    break;
+                }
+                _ => escaped = false,
+            }
+        }
+        let end = end?;
+        output.push_str(&after[..end]);
+        found = true;
+        rest = &after[end + 1..];
+    }
+    found.then(|| {
+        output
+            .replace("\\n", " ")
+            .replace("\\l", " ")
+            .replace("\\p", " ")
+            .replace("  ", " ")
+            .trim()
+            .to_owned()
+    })
+}
+
+/// Resolves `[KEY] = sSomeText` pointer tables against `sSomeText[] = _("...")` definitions.
+fn pointed_strings(source: &str) -> HashMap<String, String> {
+    let texts: HashMap<String, String> = array_definitions(source)
+        .into_iter()
+        .chain(statics(source))
+        .filter_map(|(name, body)| string_literal(&body).map(|text| (name, text)))
+        .collect();
+    designated_entries(source)
+        .into_iter()
+        .filter_map(|(key, value)| texts.get(value.trim()).map(|text| (key, text.clone())))
+        .collect()
+}
+
+/// `static const u8 sName[] = _("...");` definitions, which have no braces.
+fn statics(source: &str) -> Vec<(String, String)> {
+    let mut definitions: Vec<(String, String)> = Vec::new();
+    for statement in source.split(';') {
+        let Some((declaration, value)) = statement.split_once("[] =") else {
+            continue;
+        };
+        let name = declaration
+            .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("");
+        if !value.trim_start().starts_with('{') {
+            definitions.push((name.to_owned(), value.to_owned()));
+        }
+    }
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn species(info: &str) -> Result<Vec<Species>, Error> {
+        let names = HashMap::from([("SPECIES_BULBASAUR".to_owned(), "Bulbasaur".to_owned())]);
+        let info = format!("const struct SpeciesInfo gSpeciesInfo[] =\n{{\n{}\n}};", info);
+        parse_species(&strip_comments(&info), &names)
+    }
+
+    #[test]
+    fn parses_a_species_entry() {
+        let species = species(
+            "[SPECIES_NONE] = {0},
+            [SPECIES_BULBASAUR] =
+            {
+                .baseHP = 45,
+                .baseAttack = 49,
+                .baseDefense = 49,
+                .baseSpeed = 45,
+                .baseSpAttack = 65,
+                .baseSpDefense = 65, // comments are ignored
+                .types = { TYPE_GRASS, TYPE_POISON },
+                .eggGroups = { EGG_GROUP_MONSTER, EGG_GROUP_GRASS },
+                .abilities = { ABILITY_OVERGROW, ABILITY_NONE, ABILITY_CHLOROPHYLL },
+            },",
+        )
+        .unwrap();
+        assert_eq!(species.len(), 1);
+        let bulbasaur = &species[0];
+        assert_eq!(bulbasaur.internal_name, "SPECIES_BULBASAUR");
+        assert_eq!(bulbasaur.name.as_deref(), Some("Bulbasaur"));
+        assert_eq!(bulbasaur.base_stats, [45, 49, 49, 65, 65, 45]);
+        assert_eq!(bulbasaur.types, ["TYPE_GRASS", "TYPE_POISON"]);
+        assert_eq!(bulbasaur.egg_groups, ["EGG_GROUP_MONSTER", "EGG_GROUP_GRASS"]);
+        assert_eq!(bulbasaur.abilities, ["ABILITY_OVERGROW", "ABILITY_CHLOROPHYLL"]);
+    }
+
+    #[test]
+    fn resolves_conditional_stats() {
+        let species = species(
+            "[SPECIES_PIKACHU] =
+            {
+                .baseHP = 35, .baseAttack = 55,
+                .baseDefense = P_UPDATED_STATS >= GEN_6 ? 40 : 30,
+                .baseSpeed = 90, .baseSpAttack = 50,
+                .baseSpDefense = (P_UPDATED_STATS >= GEN_6 ? 50 : 40),
+                .speciesName = _(\"Pikachu\"),
+            },",
+        )
+        .unwrap();
+        assert_eq!(species[0].name.as_deref(), Some("Pikachu"));
+        assert_eq!(species[0].base_stats, [35, 55, 40, 50, 50, 90]);
+    }
+
+    #[test]
+    fn rejects_malformed_stats() {
+        let error = species(
+            "[SPECIES_MISSINGNO] =
+            {
+                .baseHP = 33, .baseAttack = 136, .baseDefense = 0,
+                .baseSpeed = 29, .baseSpAttack = 6,
+                .baseSpDefense = BASE_SPDEF,
+            },",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.to_string(), "SPECIES_MISSINGNO: .baseSpDefense = BASE_SPDEF is not a number");
+        let error = species("[SPECIES_MISSINGNO] = { .baseHP = 300, .baseAttack = 136, .baseDefense = 0 },")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "SPECIES_MISSINGNO: .baseHP = 300 is not a number");
+        let error = species("[SPECIES_MISSINGNO] = { .baseHP = 33, .baseAttack = 136, .baseDefense = 0 },")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "SPECIES_MISSINGNO: .baseSpAttack is missing");
+    }
+}
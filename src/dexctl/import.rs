@@ -0,0 +1,419 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use rusqlite::{params, OptionalExtension, Transaction};
+use crate::Error;
+use super::decomp::{self, humanize, Decomp};
+
+const SPRITE_BASE: &str = "https://raw.githubusercontent.com/BelialClover/RoweRepo/main/graphics/pokemon";
+
+// Every table rebuilt by an import, shown before and after in the report.
+const TABLES: [&str; 16] = [
+    "pokemon",
+    "moves",
+    "abilities",
+    "pokemon_ability_relationships",
+    "levelup_learnsets",
+    "tmhm_learnsets",
+    "tutor_learnsets",
+    "egg_learnsets",
+    "pokemon_evolution_relationships",
+    "move_flag_relationships",
+    "sightings",
+    "locations",
+    "items",
+    "effects",
+    "flags",
+    "evolution_methods",
+];
+
+/// Rebuilds a copy of `db` at `out` from the decomp checkout at `decomp_dir` and prints what changed.
+pub fn run(decomp_dir: &Path, db: &Path, out: &Path) -> Result<(), Error> {
+    let decomp = decomp::load(decomp_dir)?;
+    println!(
+        "Read {} species, {} moves and {} sightings from {}.",
+        decomp.species.len(),
+        decomp.moves.len(),
+        decomp.sightings.len(),
+        decomp_dir.display()
+    );
+
+    let before = rusqlite::Connection::open(db)?;
+    let before_counts = table_counts(&before)?;
+    let before_snapshot = snapshot(&before)?;
+    drop(before);
+
+    std::fs::copy(db, out)?;
+    let mut conn = rusqlite::Connection::open(out)?;
+    crate::migrations::migrate(&mut conn, &crate::migrations::DEX)?;
+    let mut warnings = decomp.warnings.clone();
+    let tx = conn.transaction()?;
+    rebuild(&tx, &decomp, &mut warnings)?;
+    tx.commit()?;
+
+    let after_counts = table_counts(&conn)?;
+    let after_snapshot = snapshot(&conn)?;
+    print_diff(&before_snapshot, &after_snapshot);
+
+    println!("\nRows per table:");
+    for (table, (before, after)) in TABLES.iter().zip(before_counts.iter().zip(after_counts.iter())) {
+        println!("{0:<32} {1:>6} -> {2:<6} ({3:+})", table, before, after, after - before);
+    }
+    if !warnings.is_empty() {
+        println!("\n{} warnings:", warnings.len());
+        for warning in &warnings {
+            println!("  {}", warning);
+        }
+    }
+    println!("\nWrote {}.", out.display());
+    Ok(())
+}
+
+fn rebuild(tx: &Transaction, decomp: &Decomp, warnings: &mut Vec<String>) -> Result<(), Error> {
+    // Everything referencing pokemon or moves is rebuilt from scratch.
+    for table in [
+        "pokemon_ability_relationships",
+        "levelup_learnsets",
+        "tmhm_learnsets",
+        "tutor_learnsets",
+        "egg_learnsets",
+        "pokemon_evolution_relationships",
+        "move_flag_relationships",
+        "sightings",
+    ] {
+        tx.execute(&format!("delete from {}", table), [])?;
+    }
+
+    let mut abilities = internal_ids(tx, "abilities")?;
+    for ability in &decomp.abilities {
+        let name = ability.name.clone().unwrap_or_else(|| humanize(&ability.internal_name));
+        let description = ability.description.clone().unwrap_or_default();
+        match abilities.get(&ability.internal_name) {
+            Some(id) => {
+                tx.execute(
+                    "update abilities set description = ?1 where id = ?2",
+                    params![description, id],
+                )?;
+            }
+            None => {
+                tx.execute(
+                    "insert into abilities (name, internal_name, description) values (?1, ?2, ?3)",
+                    params![name, ability.internal_name, description],
+                )?;
+                abilities.insert(ability.internal_name.clone(), tx.last_insert_rowid());
+            }
+        }
+    }
+
+    let mut moves = internal_ids(tx, "moves")?;
+    for m in &decomp.moves {
+        let values = params![
+            lookup(tx, "types", &m.type_name)?,
+            lookup(tx, "splits", &m.split)?,
+            m.power,
+            m.accuracy,
+            m.pp,
+            lookup(tx, "effects", &m.effect)?,
+            m.chance,
+            lookup(tx, "targets", &m.target)?,
+            m.priority,
+        ];
+        match moves.get(&m.internal_name) {
+            Some(id) => {
+                tx.execute(
+                    "update moves set type = ?1, split = ?2, power = ?3, accuracy = ?4, pp = ?5, effect = ?6, chance = ?7, target = ?8, priority = ?9 where id = ?10",
+                    rusqlite::params_from_iter(values.iter().copied().chain([id as &dyn rusqlite::ToSql])),
+                )?;
+                if let Some(description) = &m.description {
+                    tx.execute(
+                        "update moves set description = ?1 where id = ?2",
+                        params![description, id],
+                    )?;
+                }
+            }
+            None => {
+                let name = m.name.clone().unwrap_or_else(|| humanize(&m.internal_name));
+                tx.execute(
+                    "insert into moves (type, split, power, accuracy, pp, effect, chance, target, priority, name, internal_name, description) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    rusqlite::params_from_iter(values.iter().copied().chain(
+                        params![name, m.internal_name, m.description].iter().copied(),
+                    )),
+                )?;
+                moves.insert(m.internal_name.clone(), tx.last_insert_rowid());
+            }
+        }
+        for flag in &m.flags {
+            tx.execute(
+                "insert into move_flag_relationships (move, flag) values (?1, ?2)",
+                params![moves[&m.internal_name], lookup_by_name(tx, "flags", &humanize(flag))?],
+            )?;
+        }
+    }
+
+    let mut pokemon = internal_ids(tx, "pokemon")?;
+    for species in &decomp.species {
+        let type1 = species.types.first().map(|t| lookup(tx, "types", t)).transpose()?;
+        // Single-type Pokemon repeat their type, like the rest of the table.
+        let type2 = species.types.get(1).map(|t| lookup(tx, "types", t)).transpose()?.or(type1);
+        let egg_group1 = species.egg_groups.first().map(|e| lookup(tx, "egg_groups", e)).transpose()?;
+        let egg_group2 = species.egg_groups.get(1).map(|e| lookup(tx, "egg_groups", e)).transpose()?.or(egg_group1);
+        let items: Vec<Option<i64>> = species
+            .items
+            .iter()
+            .map(|item| match item.as_str() {
+                "ITEM_NONE" => Ok(None),
+                item => lookup(tx, "items", item).map(Some),
+            })
+            .collect::<Result<_, _>>()?;
+        let [hp, atk, def, spa, spd, spe] = species.base_stats;
+        let values = params![
+            hp,
+            atk,
+            def,
+            spa,
+            spd,
+            spe,
+            type1,
+            type2,
+            egg_group1,
+            egg_group2,
+            items.first().copied().flatten(),
+            items.get(1).copied().flatten(),
+        ];
+        match pokemon.get(&species.internal_name) {
+            // Display names and sprites in rowedex.db are curated (forms, megas, names past
+            // the decomp's 10 character limit), so only new species take them from the decomp.
+            Some(id) => {
+                tx.execute(
+                    "update pokemon set base_hp = ?1, base_atk = ?2, base_def = ?3, base_spa = ?4, base_spd = ?5, base_spe = ?6, type1 = ?7, type2 = ?8, egg_group1 = ?9, egg_group2 = ?10, item1 = ?11, item2 = ?12 where id = ?13",
+                    rusqlite::params_from_iter(values.iter().copied().chain([id as &dyn rusqlite::ToSql])),
+                )?;
+            }
+            None => {
+                let folder = species.internal_name.trim_start_matches("SPECIES_").to_lowercase();
+                let name = species.name.clone().unwrap_or_else(|| humanize(&species.internal_name));
+                let sprite = format!("{}/{}/front.png", SPRITE_BASE, folder);
+                tx.execute(
+                    "insert into pokemon (base_hp, base_atk, base_def, base_spa, base_spd, base_spe, type1, type2, egg_group1, egg_group2, item1, item2, name, internal_name, sprite) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                    rusqlite::params_from_iter(values.iter().copied().chain(
+                        params![name, species.internal_name, sprite].iter().copied(),
+                    )),
+                )?;
+                pokemon.insert(species.internal_name.clone(), tx.last_insert_rowid());
+            }
+        }
+        for ability in &species.abilities {
+            match abilities.get(ability) {
+                Some(ability_id) => {
+                    tx.execute(
+                        "insert into pokemon_ability_relationships (pokemon, ability) values (?1, ?2)",
+                        params![pokemon[&species.internal_name], ability_id],
+                    )?;
+                }
+                None => warnings.push(format!("{} has unknown ability {}.", species.internal_name, ability)),
+            }
+        }
+    }
+
+    // Rows the decomp no longer has. The None rows are kept as every table's placeholder.
+    let species: BTreeSet<&str> = decomp.species.iter().map(|s| s.internal_name.as_str()).collect();
+    for (internal_name, id) in pokemon.clone() {
+        if internal_name != "SPECIES_NONE" && !species.contains(internal_name.as_str()) {
+            tx.execute("delete from pokemon where id = ?1", [id])?;
+            pokemon.remove(&internal_name);
+        }
+    }
+    let decomp_moves: BTreeSet<&str> = decomp.moves.iter().map(|m| m.internal_name.as_str()).collect();
+    for (internal_name, id) in moves.clone() {
+        if internal_name != "MOVE_NONE" && !decomp_moves.contains(internal_name.as_str()) {
+            tx.execute("delete from moves where id = ?1", [id])?;
+            moves.remove(&internal_name);
+        }
+    }
+
+    let mut learnset = |table: &str, species: &str, move_name: &str, level: Option<u8>| -> Result<(), Error> {
+        let (Some(pokemon_id), Some(move_id)) = (pokemon.get(species), moves.get(move_name)) else {
+            warnings.push(format!("Skipped {} {} for {}.", table, move_name, species));
+            return Ok(());
+        };
+        match level {
+            Some(level) => tx.execute(
+                &format!("insert into {} (pokemon, move, level) values (?1, ?2, ?3)", table),
+                params![pokemon_id, move_id, level],
+            )?,
+            None => tx.execute(
+                &format!("insert into {} (pokemon, move) values (?1, ?2)", table),
+                params![pokemon_id, move_id],
+            )?,
+        };
+        Ok(())
+    };
+    for (species, set) in &decomp.levelup_learnsets {
+        for (level, move_name) in set {
+            learnset("levelup_learnsets", species, move_name, Some(*level))?;
+        }
+    }
+    for (table, sets) in [
+        ("tmhm_learnsets", &decomp.tmhm_learnsets),
+        ("tutor_learnsets", &decomp.tutor_learnsets),
+        ("egg_learnsets", &decomp.egg_learnsets),
+    ] {
+        for (species, set) in sets {
+            for move_name in set {
+                learnset(table, species, move_name, None)?;
+            }
+        }
+    }
+
+    for evolution in &decomp.evolutions {
+        let (Some(pokemon_id), Some(evolved_id)) = (pokemon.get(&evolution.species), pokemon.get(&evolution.evolved)) else {
+            warnings.push(format!("Skipped evolution {} -> {}.", evolution.species, evolution.evolved));
+            continue;
+        };
+        let param = evolution.param.as_str();
+        let (mut total, mut item, mut move_id, mut location, mut type_id, mut other) = (None, None, None, None, None, None);
+        if let Ok(number) = param.parse::<i64>() {
+            total = Some(number);
+        } else if param.starts_with("ITEM_") {
+            item = Some(lookup(tx, "items", param)?);
+        } else if param.starts_with("MOVE_") {
+            move_id = moves.get(param).copied();
+        } else if param.starts_with("TYPE_") {
+            type_id = Some(lookup(tx, "types", param)?);
+        } else if param.starts_with("SPECIES_") {
+            other = pokemon.get(param).copied();
+        } else if param.starts_with("MAP") {
+            location = Some(lookup_by_name(tx, "locations", &humanize(param))?);
+        } else {
+            warnings.push(format!("Unknown evolution parameter {} for {}.", param, evolution.species));
+        }
+        tx.execute(
+            "insert into pokemon_evolution_relationships (pokemon, evolved, evolution_method, total, item, move, location, type, pokemon_other) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                pokemon_id,
+                evolved_id,
+                lookup(tx, "evolution_methods", &evolution.method)?,
+                total,
+                item,
+                move_id,
+                location,
+                type_id,
+                other
+            ],
+        )?;
+    }
+
+    for sighting in &decomp.sightings {
+        let Some(pokemon_id) = pokemon.get(&sighting.species) else {
+            warnings.push(format!("Skipped sighting of unknown {} on {}.", sighting.species, sighting.map));
+            continue;
+        };
+        tx.execute(
+            "insert into sightings (location, encounter_method, pokemon, chance) values (?1, ?2, ?3, ?4)",
+            params![
+                lookup_by_name(tx, "locations", &humanize(&sighting.map))?,
+                lookup_by_name(tx, "encounter_methods", &sighting.encounter_method)?,
+                pokemon_id,
+                sighting.chance
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+fn internal_ids(tx: &Transaction, table: &str) -> Result<HashMap<String, i64>, rusqlite::Error> {
+    let mut stmt = tx.prepare(&format!("select internal_name, id from {} where internal_name is not null", table))?;
+    let ids = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect();
+    ids
+}
+
+/// Id of a lookup table row by internal name, adding the row if the decomp introduced it.
+fn lookup(tx: &Transaction, table: &str, internal_name: &str) -> Result<i64, rusqlite::Error> {
+    let existing = tx
+        .query_row(
+            &format!("select id from {} where internal_name = ?1", table),
+            [internal_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    tx.execute(
+        &format!("insert into {} (name, internal_name) values (?1, ?2)", table),
+        [humanize(internal_name), internal_name.to_owned()],
+    )?;
+    Ok(tx.last_insert_rowid())
+}
+
+/// Like `lookup`, for the tables without internal names (locations, encounter methods and flags).
+fn lookup_by_name(tx: &Transaction, table: &str, name: &str) -> Result<i64, rusqlite::Error> {
+    let existing = tx
+        .query_row(&format!("select id from {} where name = ?1", table), [name], |row| row.get(0))
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    tx.execute(&format!("insert into {} (name) values (?1)", table), [name])?;
+    Ok(tx.last_insert_rowid())
+}
+
+fn table_counts(conn: &rusqlite::Connection) -> Result<Vec<i64>, rusqlite::Error> {
+    TABLES
+        .iter()
+        .map(|table| conn.query_row(&format!("select count(*) from {}", table), [], |row| row.get(0)))
+        .collect()
+}
+
+type Snapshot = BTreeSet<(String, String, String)>;
+
+/// Every fact in the database as (entity, attribute, value), keyed by internal names
+/// so ids shifting between imports don't show up as changes.
+fn snapshot(conn: &rusqlite::Connection) -> Result<Snapshot, Error> {
+    let sql = crate::dex::read_query("./src/dexctl/queries/snapshot.sql");
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map([], |row| {
+            let value = match row.get::<_, rusqlite::types::Value>(2)? {
+                rusqlite::types::Value::Text(text) => text,
+                rusqlite::types::Value::Integer(number) => number.to_string(),
+                rusqlite::types::Value::Null => "".to_owned(),
+                other => format!("{:?}", other),
+            };
+            Ok((row.get(0)?, row.get(1)?, value))
+        })?
+        .collect::<Result<Snapshot, _>>()?;
+    Ok(rows)
+}
+
+fn print_diff(before: &Snapshot, after: &Snapshot) {
+    let mut changes: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (entity, attribute, value) in before.difference(after) {
+        changes.entry(entity).or_default().push(format!("  - {}: {}", attribute, value));
+    }
+    for (entity, attribute, value) in after.difference(before) {
+        changes.entry(entity).or_default().push(format!("  + {}: {}", attribute, value));
+    }
+    let entities = |snapshot: &Snapshot| -> BTreeSet<String> {
+        snapshot.iter().map(|(entity, _, _)| entity.clone()).collect()
+    };
+    let (before_entities, after_entities) = (entities(before), entities(after));
+
+    if changes.is_empty() {
+        println!("No changes.");
+        return;
+    }
+    println!("{} changed entries:", changes.len());
+    for (entity, lines) in changes {
+        let status = match (before_entities.contains(entity), after_entities.contains(entity)) {
+            (false, true) => " (added)",
+            (true, false) => " (removed)",
+            _ => "",
+        };
+        println!("{}{}", entity, status);
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+}
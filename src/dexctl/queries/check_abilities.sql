@@ -0,0 +1,9 @@
+-- Placeholder species have a base_total of 0 and are never shown.
+select
+    'pokemon ' || p.internal_name,
+    'has no abilities'
+from
+    pokemon p
+where
+    p.base_total > 0
+    and not exists (select 1 from pokemon_ability_relationships par where par.pokemon = p.id)
@@ -0,0 +1,22 @@
+select
+    'types ' || attacking.internal_name || ' -> ' || defending.internal_name,
+    count(bt.id) || ' base_types rows instead of 1'
+from
+    types attacking
+cross join
+    types defending
+left join
+    base_types bt on (bt.base_type = attacking.id and bt.type = defending.id)
+group by
+    attacking.id,
+    defending.id
+having
+    count(bt.id) <> 1
+union all
+select
+    'types',
+    count(*) || ' types instead of 18'
+from
+    types
+having
+    count(*) <> 18
@@ -0,0 +1,7 @@
+select
+    fk."table" || ' ' || fk.rowid,
+    'references a missing ' || fk.parent || ' row'
+from
+    pragma_foreign_key_check() fk
+where
+    fk."table" <> 'schema_version'
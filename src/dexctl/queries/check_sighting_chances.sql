@@ -0,0 +1,14 @@
+select
+    'location ' || l.name || ' (' || em.name || ')',
+    'encounter chances add up to ' || sum(s.chance) || '%'
+from
+    sightings s
+inner join
+    locations l on (l.id = s.location)
+inner join
+    encounter_methods em on (em.id = s.encounter_method)
+group by
+    s.location,
+    s.encounter_method
+having
+    sum(s.chance) <> 100
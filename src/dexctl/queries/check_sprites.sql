@@ -0,0 +1,13 @@
+-- Placeholder species have a base_total of 0 and are never shown.
+select
+    'pokemon ' || internal_name,
+    'has a missing or malformed sprite URL: ' || coalesce(nullif(sprite, ''), 'none')
+from
+    pokemon
+where
+    base_total > 0
+    and (
+        nullif(sprite, '') is null
+        or sprite not like 'https://%/%.png'
+        or sprite glob '*[^A-Za-z0-9./:_%~-]*'
+    )
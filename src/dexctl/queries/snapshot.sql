@@ -0,0 +1,35 @@
+select 'pokemon ' || p.internal_name, 'name', p.name from pokemon p
+union all select 'pokemon ' || p.internal_name, 'base stats', p.base_hp || '/' || p.base_atk || '/' || p.base_def || '/' || p.base_spa || '/' || p.base_spd || '/' || p.base_spe from pokemon p
+union all select 'pokemon ' || p.internal_name, 'types', coalesce(t1.internal_name, '') || ' ' || coalesce(t2.internal_name, '') from pokemon p left join types t1 on p.type1 = t1.id left join types t2 on p.type2 = t2.id
+union all select 'pokemon ' || p.internal_name, 'egg groups', coalesce(e1.internal_name, '') || ' ' || coalesce(e2.internal_name, '') from pokemon p left join egg_groups e1 on p.egg_group1 = e1.id left join egg_groups e2 on p.egg_group2 = e2.id
+union all select 'pokemon ' || p.internal_name, 'items', coalesce(i1.internal_name, '') || ' ' || coalesce(i2.internal_name, '') from pokemon p left join items i1 on p.item1 = i1.id left join items i2 on p.item2 = i2.id
+union all select 'pokemon ' || p.internal_name, 'ability', a.internal_name from pokemon_ability_relationships par join pokemon p on par.pokemon = p.id join abilities a on par.ability = a.id
+union all select 'pokemon ' || p.internal_name, 'level up', l.level || ' ' || m.internal_name from levelup_learnsets l join pokemon p on l.pokemon = p.id join moves m on l.move = m.id
+union all select 'pokemon ' || p.internal_name, 'tm/hm', m.internal_name from tmhm_learnsets l join pokemon p on l.pokemon = p.id join moves m on l.move = m.id
+union all select 'pokemon ' || p.internal_name, 'tutor', m.internal_name from tutor_learnsets l join pokemon p on l.pokemon = p.id join moves m on l.move = m.id
+union all select 'pokemon ' || p.internal_name, 'egg move', m.internal_name from egg_learnsets l join pokemon p on l.pokemon = p.id join moves m on l.move = m.id
+union all select 'pokemon ' || p.internal_name, 'evolution', em.internal_name || ' ' || coalesce(per.total, i.internal_name, m.internal_name, l.name, t.internal_name, o.internal_name, '') || ' -> ' || e.internal_name
+    from pokemon_evolution_relationships per
+    join pokemon p on per.pokemon = p.id
+    join pokemon e on per.evolved = e.id
+    join evolution_methods em on per.evolution_method = em.id
+    left join items i on per.item = i.id
+    left join moves m on per.move = m.id
+    left join locations l on per.location = l.id
+    left join types t on per.type = t.id
+    left join pokemon o on per.pokemon_other = o.id
+union all select 'move ' || m.internal_name, 'name', m.name from moves m
+union all select 'move ' || m.internal_name, 'type', t.internal_name from moves m join types t on m.type = t.id
+union all select 'move ' || m.internal_name, 'split', s.internal_name from moves m join splits s on m.split = s.id
+union all select 'move ' || m.internal_name, 'effect', e.internal_name from moves m join effects e on m.effect = e.id
+union all select 'move ' || m.internal_name, 'target', t.internal_name from moves m join targets t on m.target = t.id
+union all select 'move ' || m.internal_name, 'power/accuracy/pp', coalesce(m.power, 0) || '/' || coalesce(m.accuracy, 0) || '/' || coalesce(m.pp, 0) from moves m
+union all select 'move ' || m.internal_name, 'chance/priority', coalesce(m.chance, 0) || '/' || coalesce(m.priority, 0) from moves m
+union all select 'move ' || m.internal_name, 'description', coalesce(m.description, '') from moves m
+union all select 'move ' || m.internal_name, 'flag', f.name from move_flag_relationships mfr join moves m on mfr.move = m.id join flags f on mfr.flag = f.id
+union all select 'ability ' || a.internal_name, 'name', a.name || ': ' || a.description from abilities a
+union all select 'location ' || l.name, 'sighting', em.name || ' ' || p.internal_name || ' ' || s.chance || '%'
+    from sightings s
+    join locations l on s.location = l.id
+    join encounter_methods em on s.encounter_method = em.id
+    join pokemon p on s.pokemon = p.id
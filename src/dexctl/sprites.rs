@@ -0,0 +1,190 @@
+//! Imports the sprites of a decomp checkout into the sprite store, see `crate::sprites`.
+//!
+//! Every folder under `graphics/pokemon` with a `front.png` or `anim_front.png` is a species. Its front, back and
+//! icon sprites are converted to transparent PNGs, and its shiny sprites are made by applying `shiny.pal`
+//! to the front and back ones, as the games do.
+
+use image::{Rgba, RgbaImage};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use crate::sprites::Variant;
+use crate::Error;
+
+/// A paletted PNG, as the decomp's sprites are.
+struct Indexed {
+    width: u32,
+    height: u32,
+    indices: Vec<u8>,
+    palette: Vec<[u8; 3]>,
+}
+
+/// Imports every species' sprites from `decomp_dir` into `out` and prints how many of each variant were found.
+pub fn run(decomp_dir: &Path, out: &Path) -> Result<(), Error> {
+    let graphics = decomp_dir.join("graphics/pokemon");
+    if !graphics.is_dir() {
+        return Err(format!("{} does not exist.", graphics.display()).into());
+    }
+    let mut species = Vec::new();
+    find_species(&graphics, &graphics, &mut species)?;
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut warnings = Vec::new();
+    for relative in &species {
+        let dir = graphics.join(relative);
+        let target = out.join(relative);
+        std::fs::create_dir_all(&target)?;
+        for variant in Variant::ALL {
+            match import(&dir, variant) {
+                Ok(Some(sprite)) => {
+                    sprite.save(target.join(variant.file_name()))?;
+                    *counts.entry(variant.file_name()).or_default() += 1;
+                }
+                Ok(None) => {}
+                Err(e) => warnings.push(format!("{}: {}: {}", relative, variant.file_name(), e)),
+            }
+        }
+    }
+    println!("Imported the sprites of {} species from {} into {}.", species.len(), graphics.display(), out.display());
+    for variant in Variant::ALL {
+        println!(
+            "{0:<16} {1:>5}",
+            variant.file_name(),
+            counts.get(variant.file_name()).copied().unwrap_or(0)
+        );
+    }
+    if !warnings.is_empty() {
+        println!("\n{} warnings:", warnings.len());
+        for warning in warnings {
+            println!("  {}", warning);
+        }
+    }
+    Ok(())
+}
+
+/// Collects the folders holding a species' sprites, relative to `root` with `/` separators.
+fn find_species(root: &Path, dir: &Path, species: &mut Vec<String>) -> Result<(), Error> {
+    if dir.join("front.png").is_file() || dir.join("anim_front.png").is_file() {
+        let relative = dir.strip_prefix(root)?;
+        if !relative.as_os_str().is_empty() {
+            species.push(relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"));
+        }
+    }
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.flatten().map(|entry| entry.path()).collect();
+    entries.sort();
+    for entry in entries.into_iter().filter(|entry| entry.is_dir()) {
+        find_species(root, &entry, species)?;
+    }
+    Ok(())
+}
+
+/// One variant of a species' sprite, or None if the decomp does not have it.
+fn import(dir: &Path, variant: Variant) -> Result<Option<RgbaImage>, Error> {
+    let (file, shiny) = match variant {
+        Variant::Front | Variant::ShinyFront => {
+            let file = if dir.join("front.png").is_file() { "front.png" } else { "anim_front.png" };
+            (file, variant == Variant::ShinyFront)
+        }
+        Variant::Back | Variant::ShinyBack => ("back.png", variant == Variant::ShinyBack),
+        Variant::Icon => ("icon.png", false),
+    };
+    let path = dir.join(file);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let palette = if shiny {
+        let pal = dir.join("shiny.pal");
+        if !pal.is_file() {
+            return Ok(None);
+        }
+        Some(read_pal(&pal)?)
+    } else if dir.join("normal.pal").is_file() && variant != Variant::Icon {
+        // Icons use shared palettes, kept in the PNG.
+        Some(read_pal(&dir.join("normal.pal"))?)
+    } else {
+        None
+    };
+    let sprite = match read_indexed(&path)? {
+        Some(indexed) => indexed.to_rgba(palette.as_deref().unwrap_or(&indexed.palette)),
+        // Shiny sprites cannot be made without the palette indices.
+        None if shiny => return Ok(None),
+        None => image::open(&path)?.to_rgba8(),
+    };
+    Ok(Some(first_frame(sprite)))
+}
+
+/// Animated sprites and icons stack their frames vertically, only the first one is kept.
+fn first_frame(sprite: RgbaImage) -> RgbaImage {
+    if sprite.height() >= 2 * sprite.width() {
+        image::imageops::crop_imm(&sprite, 0, 0, sprite.width(), sprite.width()).to_image()
+    } else {
+        sprite
+    }
+}
+
+/// Reads a paletted PNG, or None if it is not one.
+fn read_indexed(path: &Path) -> Result<Option<Indexed>, Error> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder.read_info()?;
+    let Some(palette) = reader.info().palette.as_ref().map(|palette| {
+        palette.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect::<Vec<[u8; 3]>>()
+    }) else {
+        return Ok(None);
+    };
+    let mut buffer = vec![0; reader.output_buffer_size().ok_or("PNG too large")?];
+    let frame = reader.next_frame(&mut buffer)?;
+    if frame.color_type != png::ColorType::Indexed {
+        return Ok(None);
+    }
+    let bits = frame.bit_depth as usize;
+    let mut indices = Vec::with_capacity((frame.width * frame.height) as usize);
+    for line in buffer.chunks(frame.line_size).take(frame.height as usize) {
+        for x in 0..frame.width as usize {
+            let bit = x * bits;
+            let shift = 8 - bits - bit % 8;
+            indices.push((line[bit / 8] >> shift) & ((1u16 << bits) - 1) as u8);
+        }
+    }
+    Ok(Some(Indexed {
+        width: frame.width,
+        height: frame.height,
+        indices,
+        palette,
+    }))
+}
+
+/// Reads a JASC-PAL palette, as the decomp's `.pal` files are.
+fn read_pal(path: &Path) -> Result<Vec<[u8; 3]>, Error> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut lines = contents.lines();
+    if lines.next().map(str::trim) != Some("JASC-PAL") {
+        return Err(format!("{} is not a JASC-PAL palette.", path.display()).into());
+    }
+    // Version and color count.
+    lines.next();
+    lines.next();
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let rgb: Vec<u8> = line.split_whitespace().map(str::parse).collect::<Result<_, _>>()?;
+            match rgb[..] {
+                [r, g, b] => Ok([r, g, b]),
+                _ => Err(format!("{}: {} is not a color.", path.display(), line).into()),
+            }
+        })
+        .collect()
+}
+
+impl Indexed {
+    /// Colors the sprite with `palette`, the first color being the transparent background.
+    fn to_rgba(&self, palette: &[[u8; 3]]) -> RgbaImage {
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let index = self.indices[(y * self.width + x) as usize] as usize;
+            match palette.get(index) {
+                Some([r, g, b]) if index != 0 => Rgba([*r, *g, *b, 255]),
+                _ => Rgba([0, 0, 0, 0]),
+            }
+        })
+    }
+}
@@ -1,62 +1,118 @@
 use crate::serenity::{EventHandler, Interaction};
 use poise::serenity_prelude as serenity;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::Instrument;
 
+use crate::analytics;
+use crate::datasets::Datasets;
+use crate::lifecycle::{self, Lifecycle};
+use crate::metrics;
+use crate::settings;
 use crate::dex::type_effectiveness::type_effectiveness_component;
 use crate::dex::levelup::levelup_component;
 use crate::dex::hmtm::hmtm_component;
 use crate::dex::tutor::tutor_component;
 use crate::dex::eggmoves::eggmoves_component;
+use crate::dex::stats::stats_component;
+use crate::dex::sprite::{self, sprite_component};
 
-pub struct Handler;
+pub struct Handler {
+    pub datasets: Arc<Datasets>,
+    pub lifecycle: Arc<Lifecycle>,
+}
 #[serenity::async_trait]
 impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: serenity::Context, i: Interaction) {
-        match i {
-            Interaction::Component(i) => {
-                match &i.data.custom_id.split_once("__") {
-                    Some(("typeeffectiveness_btn", pokemon_id)) => {
-                        type_effectiveness_component(
-                            ctx,
-                            &i,
-                            pokemon_id.parse::<u16>().unwrap(),
-                        )
-                        .await
-                        .unwrap()
-                    }
-                    Some(("levelup_btn", pokemon_id)) => levelup_component(
-                        ctx,
-                        &i,
-                        pokemon_id.parse::<u16>().unwrap(),
-                    )
-                    .await
-                    .unwrap(),
-                    Some(("hmtm_btn", pokemon_id)) => hmtm_component(
-                        ctx,
-                        &i,
-                        pokemon_id.parse::<u16>().unwrap(),
-                    )
-                    .await
-                    .unwrap(),
-                    Some(("tutor_btn", pokemon_id)) => tutor_component(
-                        ctx,
-                        &i,
-                        pokemon_id.parse::<u16>().unwrap(),
-                    )
-                    .await
-                    .unwrap(),
-                    Some(("eggmoves_btn", pokemon_id)) => eggmoves_component(
-                        ctx,
-                        &i,
-                        pokemon_id.parse::<u16>().unwrap(),
-                    )
-                    .await
-                    .unwrap(),
-                    Some((&_, _)) => (),
-                    None => (),
+        if let Interaction::Component(i) = i {
+            // Dex buttons are "<button>__<pokemon id>__<dataset>", see dex::button_id.
+            let Some((button, rest)) = i.data.custom_id.split_once("__") else {
+                return;
+            };
+            let (pokemon_id, dataset) = match rest.split_once("__") {
+                Some((pokemon_id, name)) => (pokemon_id, self.datasets.get(name)),
+                // Buttons sent before datasets existed have no dataset.
+                None => (rest, Some(self.datasets.default())),
+            };
+            let (Ok(pokemon_id), Some(dataset)) = (pokemon_id.parse::<u16>(), dataset) else {
+                return;
+            };
+            let in_flight = self.lifecycle.begin();
+            let blocked = match (settings::is_user_blocked(i.user.id), settings::is_channel_blocked(i.channel_id)) {
+                (Ok(user_blocked), Ok(channel_blocked)) => user_blocked || channel_blocked,
+                // Refused, as commands are when their check fails, rather than letting blocked users through.
+                (Err(e), _) | (_, Err(e)) => {
+                    tracing::error!(user_id = i.user.id.get(), channel_id = i.channel_id.get(), error = %e, "Failed to read the block lists");
+                    true
+                }
+            };
+            let refusal = if in_flight.is_none() {
+                Some(lifecycle::SHUTTING_DOWN_MESSAGE)
+            } else if blocked {
+                Some("You can't use this bot here.")
+            } else {
+                None
+            };
+            if let Some(refusal) = refusal {
+                let response = serenity::CreateInteractionResponse::Message(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .ephemeral(true)
+                        .content(refusal),
+                );
+                if let Err(e) = i.create_response(ctx, response).await {
+                    tracing::warn!(user_id = i.user.id.get(), channel_id = i.channel_id.get(), error = %e, "Failed to refuse a button");
+                }
+                return;
+            }
+            let span = tracing::info_span!(
+                "component",
+                guild_id = i.guild_id.map(|guild_id| guild_id.get()),
+                channel_id = i.channel_id.get(),
+                user_id = i.user.id.get(),
+                button,
+                pokemon_id,
+                dataset = %dataset.name,
+            );
+            let started = Instant::now();
+            async {
+                let result = match button {
+                    "typeeffectiveness_btn" => type_effectiveness_component(ctx, &i, &dataset, pokemon_id).await,
+                    "levelup_btn" => levelup_component(ctx, &i, &dataset, pokemon_id).await,
+                    "hmtm_btn" => hmtm_component(ctx, &i, &dataset, pokemon_id).await,
+                    "tutor_btn" => tutor_component(ctx, &i, &dataset, pokemon_id).await,
+                    "eggmoves_btn" => eggmoves_component(ctx, &i, &dataset, pokemon_id).await,
+                    "stats_btn" => stats_component(ctx, &i, &dataset, pokemon_id).await,
+                    sprite::MENU_ID => sprite_component(ctx, &i, &dataset, pokemon_id).await,
+                    _ => return,
                 };
-                ()
+                let duration_ms = started.elapsed().as_millis() as u64;
+                analytics::record(&analytics::Event {
+                    kind: "button",
+                    name: button,
+                    guild_id: i.guild_id,
+                    dataset: Some(&dataset.name),
+                    pokemon_id: Some(pokemon_id),
+                    duration_ms,
+                    success: result.is_ok(),
+                });
+                metrics::component(button, result.is_ok());
+                match result {
+                    Ok(()) => tracing::info!(duration_ms, "Handled button"),
+                    Err(e) => {
+                        metrics::ERRORS.with_label_values(&["component"]).inc();
+                        tracing::error!(duration_ms, error = ?e, "Button failed")
+                    }
+                }
             }
-            _ => {}
+            .instrument(span)
+            .await;
+        }
+    }
+
+    async fn shard_stage_update(&self, _ctx: serenity::Context, event: serenity::ShardStageUpdateEvent) {
+        if event.old == serenity::ConnectionStage::Connected {
+            tracing::warn!(shard = %event.shard_id, new = %event.new, "Lost gateway connection");
+            metrics::GATEWAY_RECONNECTS.inc();
         }
     }
 }
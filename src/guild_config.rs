@@ -0,0 +1,359 @@
+//! Per-guild configuration, edited by the guild's admins with `/config` and stored in settings.db.
+
+use poise::serenity_prelude as serenity;
+use poise::ChoiceParameter;
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+use crate::dex::{reply, DexButton};
+use crate::{config, settings, Context, Data, Error};
+
+const MAX_PREFIX_LENGTH: usize = 10;
+
+pub struct GuildConfig {
+    pub prefix: String,
+    /// Whether dex replies are visible to everyone instead of only the user.
+    pub public_replies: bool,
+    pub style: DexStyle,
+    /// Buttons shown under dex replies.
+    pub buttons: Vec<DexButton>,
+    pub language: Language,
+}
+
+impl GuildConfig {
+    /// Config of a guild, or the defaults in DMs.
+    pub fn get(guild_id: Option<serenity::GuildId>) -> Result<GuildConfig, rusqlite::Error> {
+        let settings = match guild_id {
+            Some(guild_id) => settings::get_guild_settings(guild_id)?,
+            None => settings::GuildSettings::default(),
+        };
+        let hidden: Vec<&str> = settings.hidden_buttons.split_whitespace().collect();
+        Ok(GuildConfig {
+            prefix: settings.prefix.unwrap_or_else(|| config::get().prefix.clone()),
+            public_replies: settings.public_replies,
+            style: if settings.dex_cards { DexStyle::Card } else { DexStyle::Embed },
+            buttons: DexButton::ALL
+                .into_iter()
+                .filter(|button| !hidden.contains(&button.id()))
+                .collect(),
+            language: settings
+                .language
+                .as_deref()
+                .and_then(Language::from_code)
+                .unwrap_or(Language::English),
+        })
+    }
+}
+
+/// Prefix of every guild that sent a message since startup, so settings.db is not read for each message.
+/// `/config prefix` is the only thing that changes a prefix, and it updates the cache too.
+static PREFIXES: LazyLock<RwLock<HashMap<serenity::GuildId, String>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Prefix of prefix commands in the guild a message was sent in.
+pub async fn dynamic_prefix(ctx: poise::PartialContext<'_, Data, Error>) -> Result<Option<String>, Error> {
+    let Some(guild_id) = ctx.guild_id else {
+        return Ok(Some(config::get().prefix.clone()));
+    };
+    if let Some(prefix) = PREFIXES.read().unwrap().get(&guild_id) {
+        return Ok(Some(prefix.clone()));
+    }
+    let prefix = GuildConfig::get(Some(guild_id))?.prefix;
+    PREFIXES.write().unwrap().insert(guild_id, prefix.clone());
+    Ok(Some(prefix))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum Language {
+    #[name = "English"]
+    English,
+    #[name = "Español"]
+    Spanish,
+    #[name = "Français"]
+    French,
+    #[name = "Deutsch"]
+    German,
+}
+
+/// Text of the dex reply in one language. Names from the database are not translated.
+pub struct Labels {
+    pub types: &'static str,
+    pub abilities: &'static str,
+    pub held_items: &'static str,
+    pub egg_groups: &'static str,
+    pub stats: &'static str,
+    pub none: &'static str,
+    pub type_effectiveness: &'static str,
+    pub level_up: &'static str,
+    pub hm_tm: &'static str,
+    pub tutor: &'static str,
+    pub egg_moves: &'static str,
+    pub stat_ranges: &'static str,
+    pub front: &'static str,
+    pub back: &'static str,
+    pub shiny_front: &'static str,
+    pub shiny_back: &'static str,
+    pub ranked_among_all: &'static str,
+    pub ranked_among_fully_evolved: &'static str,
+    pub locations: &'static str,
+    pub pokemon_of_the_day: &'static str,
+}
+
+impl Language {
+    /// Code stored in settings.db.
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::French => "fr",
+            Language::German => "de",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Language> {
+        [Language::English, Language::Spanish, Language::French, Language::German]
+            .into_iter()
+            .find(|language| language.code() == code)
+    }
+
+    pub fn labels(self) -> &'static Labels {
+        match self {
+            Language::English => &Labels {
+                types: "Types",
+                abilities: "Abilities",
+                held_items: "Held Items",
+                egg_groups: "Egg Groups",
+                stats: "Stats",
+                none: "None",
+                type_effectiveness: "Type Effectiveness",
+                level_up: "Level-Up",
+                hm_tm: "HM/TM",
+                tutor: "Tutor",
+                egg_moves: "Egg Moves",
+                stat_ranges: "Stat Ranges",
+                front: "Front",
+                back: "Back",
+                shiny_front: "Shiny",
+                shiny_back: "Shiny (back)",
+                ranked_among_all: "Percentage of all Pokemon with a lower stat, and BST rank.",
+                ranked_among_fully_evolved: "Percentage of fully evolved Pokemon with a lower stat, and BST rank.",
+                locations: "Locations",
+                pokemon_of_the_day: "Pokemon of the day",
+            },
+            Language::Spanish => &Labels {
+                types: "Tipos",
+                abilities: "Habilidades",
+                held_items: "Objetos equipados",
+                egg_groups: "Grupos Huevo",
+                stats: "Estadísticas",
+                none: "Ninguno",
+                type_effectiveness: "Eficacia de tipos",
+                level_up: "Por nivel",
+                hm_tm: "MO/MT",
+                tutor: "Tutor",
+                egg_moves: "Movimientos Huevo",
+                stat_ranges: "Rangos de estadísticas",
+                front: "Frente",
+                back: "Espalda",
+                shiny_front: "Variocolor",
+                shiny_back: "Variocolor (espalda)",
+                ranked_among_all: "Porcentaje de todos los Pokémon con una estadística menor, y puesto por total.",
+                ranked_among_fully_evolved: "Porcentaje de los Pokémon totalmente evolucionados con una estadística menor, y puesto por total.",
+                locations: "Ubicaciones",
+                pokemon_of_the_day: "Pokémon del día",
+            },
+            Language::French => &Labels {
+                types: "Types",
+                abilities: "Talents",
+                held_items: "Objets tenus",
+                egg_groups: "Groupes Œuf",
+                stats: "Statistiques",
+                none: "Aucun",
+                type_effectiveness: "Efficacité des types",
+                level_up: "Par niveau",
+                hm_tm: "CS/CT",
+                tutor: "Donneur de capacités",
+                egg_moves: "Capacités Œuf",
+                stat_ranges: "Plages de statistiques",
+                front: "Face",
+                back: "Dos",
+                shiny_front: "Chromatique",
+                shiny_back: "Chromatique (dos)",
+                ranked_among_all: "Pourcentage de tous les Pokémon ayant une statistique inférieure, et rang du total.",
+                ranked_among_fully_evolved: "Pourcentage des Pokémon au dernier stade ayant une statistique inférieure, et rang du total.",
+                locations: "Emplacements",
+                pokemon_of_the_day: "Pokémon du jour",
+            },
+            Language::German => &Labels {
+                types: "Typen",
+                abilities: "Fähigkeiten",
+                held_items: "Getragene Items",
+                egg_groups: "Ei-Gruppen",
+                stats: "Statuswerte",
+                none: "Keine",
+                type_effectiveness: "Typ-Effektivität",
+                level_up: "Level-Up",
+                hm_tm: "VM/TM",
+                tutor: "Attacken-Lehrer",
+                egg_moves: "Ei-Attacken",
+                stat_ranges: "Statuswert-Spannen",
+                front: "Vorne",
+                back: "Hinten",
+                shiny_front: "Schillernd",
+                shiny_back: "Schillernd (hinten)",
+                ranked_among_all: "Anteil aller Pokémon mit niedrigerem Wert, und Rang der Summe.",
+                ranked_among_fully_evolved: "Anteil aller voll entwickelten Pokémon mit niedrigerem Wert, und Rang der Summe.",
+                locations: "Fundorte",
+                pokemon_of_the_day: "Pokémon des Tages",
+            },
+        }
+    }
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum Visibility {
+    #[name = "Everyone"]
+    Public,
+    #[name = "Only the user"]
+    Private,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum DexStyle {
+    #[name = "Embed"]
+    Embed,
+    #[name = "Image card"]
+    Card,
+}
+
+/// Show or change how the bot behaves in this server.
+#[poise::command(
+    slash_command,
+    guild_only,
+    check = "crate::permissions::guild_admin",
+    subcommands("show", "prefix", "replies", "style", "buttons", "language", "potd"),
+    subcommand_required
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show this server's configuration.
+#[poise::command(slash_command, guild_only)]
+pub async fn show(ctx: Context<'_>) -> Result<(), Error> {
+    let config = GuildConfig::get(ctx.guild_id())?;
+    let settings = settings::get_guild_settings(ctx.guild_id().unwrap())?;
+    let dataset = settings
+        .dataset
+        .and_then(|name| ctx.data().datasets.get(&name))
+        .unwrap_or_else(|| ctx.data().datasets.default());
+    let content = format!(
+        "Prefix: `{}`\nDex replies visible to: {}\nDex replies shown as: {}\nDex buttons: {}\nLanguage: {}\nPokemon of the day: {}\nDefault dataset: {} (change it with `/dataset set`)",
+        config.prefix,
+        if config.public_replies { Visibility::Public.name() } else { Visibility::Private.name() },
+        config.style.name(),
+        if config.buttons.is_empty() {
+            "None".to_owned()
+        } else {
+            config.buttons.iter().map(|button| button.name()).collect::<Vec<&str>>().join(", ")
+        },
+        config.language.name(),
+        settings.potd_channel.map_or("Off".to_owned(), |channel_id| format!("<#{}>", channel_id)),
+        dataset.title,
+    );
+    reply(ctx, content).await
+}
+
+/// Set the prefix of prefix commands in this server.
+#[poise::command(slash_command, guild_only)]
+pub async fn prefix(
+    ctx: Context<'_>,
+    #[description = "New prefix (defaults to ~)."] prefix: Option<String>,
+) -> Result<(), Error> {
+    let prefix = prefix.map(|prefix| prefix.trim().to_owned());
+    if let Some(prefix) = &prefix {
+        if prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_LENGTH {
+            return crate::dex::reply_error(
+                ctx,
+                format!("The prefix must be between 1 and {} characters long.", MAX_PREFIX_LENGTH),
+            )
+            .await;
+        }
+    }
+    let guild_id = ctx.guild_id().unwrap();
+    settings::set_guild_prefix(guild_id, prefix.as_deref())?;
+    let prefix = prefix.unwrap_or_else(|| config::get().prefix.clone());
+    PREFIXES.write().unwrap().insert(guild_id, prefix.clone());
+    reply(ctx, format!("Prefix commands now start with `{}`.", prefix)).await
+}
+
+/// Set whether dex replies are visible to everyone or only to the user.
+#[poise::command(slash_command, guild_only)]
+pub async fn replies(
+    ctx: Context<'_>,
+    #[description = "Who can see dex replies."] visibility: Visibility,
+) -> Result<(), Error> {
+    settings::set_guild_public_replies(ctx.guild_id().unwrap(), matches!(visibility, Visibility::Public))?;
+    reply(ctx, format!("Dex replies are now visible to: {}.", visibility.name())).await
+}
+
+/// Set whether dex replies show the Pokemon as an embed or as an image card.
+#[poise::command(slash_command, guild_only)]
+pub async fn style(
+    ctx: Context<'_>,
+    #[description = "How dex replies show the Pokemon."] style: DexStyle,
+) -> Result<(), Error> {
+    settings::set_guild_dex_cards(ctx.guild_id().unwrap(), style == DexStyle::Card)?;
+    reply(ctx, format!("Dex replies are now shown as: {}.", style.name())).await
+}
+
+/// Show or hide one of the buttons under dex replies.
+#[poise::command(slash_command, guild_only)]
+pub async fn buttons(
+    ctx: Context<'_>,
+    #[description = "Button to show or hide."] button: DexButton,
+    #[description = "Whether to show the button."] shown: bool,
+) -> Result<(), Error> {
+    let config = GuildConfig::get(ctx.guild_id())?;
+    let hidden = DexButton::ALL
+        .into_iter()
+        .filter(|other| if *other == button { !shown } else { !config.buttons.contains(other) })
+        .map(DexButton::id)
+        .collect::<Vec<&str>>()
+        .join(" ");
+    settings::set_guild_hidden_buttons(ctx.guild_id().unwrap(), &hidden)?;
+    reply(
+        ctx,
+        format!("The {} button is now {}.", button.name(), if shown { "shown" } else { "hidden" }),
+    )
+    .await
+}
+
+/// Set the language of dex replies.
+#[poise::command(slash_command, guild_only)]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "Language of dex replies."] language: Language,
+) -> Result<(), Error> {
+    settings::set_guild_language(ctx.guild_id().unwrap(), Some(language.code()))?;
+    reply(ctx, format!("Dex replies are now in {}.", language.name())).await
+}
+
+/// Set the channel the Pokemon of the day is posted in every day, or stop posting it.
+#[poise::command(slash_command, guild_only)]
+pub async fn potd(
+    ctx: Context<'_>,
+    #[description = "Channel to post in. Leave empty to stop posting."]
+    #[channel_types("Text", "News")]
+    channel: Option<serenity::GuildChannel>,
+) -> Result<(), Error> {
+    let channel_id = channel.map(|channel| channel.id);
+    settings::set_guild_potd_channel(ctx.guild_id().unwrap(), channel_id)?;
+    let content = match channel_id {
+        Some(channel_id) => format!(
+            "The Pokemon of the day is now posted in <#{}> every day at {} UTC.",
+            channel_id,
+            config::get().pokemon_of_the_day.time
+        ),
+        None => "The Pokemon of the day is no longer posted.".to_owned(),
+    };
+    reply(ctx, content).await
+}
@@ -0,0 +1,66 @@
+//! A minimal HTTP server on the `http.addr` address (e.g. `0.0.0.0:9100`), when it is set:
+//! `/metrics` for Prometheus and `/health` for orchestrators.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use crate::lifecycle::Lifecycle;
+use crate::metrics;
+
+/// Serves the endpoints until the bot stops.
+pub async fn serve(addr: SocketAddr, lifecycle: Arc<Lifecycle>) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(%addr, error = %e, "Failed to start the HTTP endpoints");
+            return;
+        }
+    };
+    tracing::info!(%addr, "Serving metrics and health");
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let lifecycle = lifecycle.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = respond(stream, &lifecycle).await {
+                        tracing::debug!(error = %e, "HTTP request failed");
+                    }
+                });
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to accept an HTTP connection"),
+        }
+    }
+}
+
+/// Answers a single HTTP request. Only the request line is looked at.
+async fn respond(mut stream: TcpStream, lifecycle: &Lifecycle) -> std::io::Result<()> {
+    let mut request = [0; 1024];
+    let read = stream.read(&mut request).await?;
+    let request_line = String::from_utf8_lossy(&request[..read]);
+    let (status, content_type, body) = match request_line.split_whitespace().take(2).collect::<Vec<&str>>()[..] {
+        ["GET", "/metrics"] => {
+            let (body, content_type) = metrics::encode().map_err(std::io::Error::other)?;
+            ("200 OK", content_type, body)
+        }
+        ["GET", "/health"] => {
+            let (healthy, report) = lifecycle.health().await;
+            let status = if healthy { "200 OK" } else { "503 Service Unavailable" };
+            (status, "application/json".to_owned(), report.to_string().into_bytes())
+        }
+        _ => ("404 Not Found", "text/plain".to_owned(), b"Not Found\n".to_vec()),
+    };
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                content_type,
+                body.len()
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
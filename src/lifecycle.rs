@@ -0,0 +1,221 @@
+//! Graceful shutdown and health reporting.
+//!
+//! On SIGTERM or Ctrl+C the bot stops taking new interactions, waits for the ones in flight to finish
+//! (up to `DRAIN_TIMEOUT`), then closes its shard connections so `main` can return.
+
+use poise::serenity_prelude as serenity;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use crate::datasets::Datasets;
+use crate::config;
+use crate::dex::read_query;
+
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+pub const SHUTTING_DOWN_MESSAGE: &str = "The bot is restarting, try again in a minute.";
+
+pub struct Lifecycle {
+    shutting_down: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+    datasets: Arc<Datasets>,
+    shard_manager: OnceLock<Arc<serenity::ShardManager>>,
+}
+
+/// Keeps the bot from shutting down until dropped.
+pub struct InFlight<'a>(&'a Lifecycle);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl Lifecycle {
+    pub fn new(datasets: Arc<Datasets>) -> Lifecycle {
+        Lifecycle {
+            shutting_down: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+            idle: Notify::new(),
+            datasets,
+            shard_manager: OnceLock::new(),
+        }
+    }
+
+    pub fn set_shard_manager(&self, shard_manager: Arc<serenity::ShardManager>) {
+        let _ = self.shard_manager.set(shard_manager);
+    }
+
+    /// Marks an interaction as in flight, or returns None if the bot is shutting down and it must be turned away.
+    pub fn begin(&self) -> Option<InFlight<'_>> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let in_flight = InFlight(self);
+        // Checked after counting it, so shutdown never misses an interaction that got through.
+        (!self.shutting_down.load(Ordering::SeqCst)).then_some(in_flight)
+    }
+
+    /// Waits for SIGTERM or Ctrl+C, then drains the interactions in flight and disconnects.
+    pub async fn shutdown_on_signal(self: Arc<Self>) {
+        wait_for_signal().await;
+        tracing::info!("Shutting down, no longer accepting interactions");
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let drained = tokio::time::timeout(DRAIN_TIMEOUT, async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight.load(Ordering::SeqCst) == 0 {
+                    break;
+                }
+                idle.await;
+            }
+        })
+        .await;
+        if drained.is_err() {
+            tracing::warn!(
+                in_flight = self.in_flight.load(Ordering::SeqCst),
+                "Interactions still in flight after {} seconds, shutting down anyway",
+                DRAIN_TIMEOUT.as_secs()
+            );
+        }
+        if let Some(shard_manager) = self.shard_manager.get() {
+            shard_manager.shutdown_all().await;
+        }
+    }
+
+    /// Gateway connectivity and database readiness, and whether both are fine.
+    pub async fn health(&self) -> (bool, serde_json::Value) {
+        let shards: Vec<(u32, serenity::ConnectionStage)> = match self.shard_manager.get() {
+            Some(shard_manager) => shard_manager
+                .runners
+                .lock()
+                .await
+                .iter()
+                .map(|(id, runner)| (id.0, runner.stage))
+                .collect(),
+            None => Vec::new(),
+        };
+        let connected = !shards.is_empty() && shards.iter().all(|(_, stage)| *stage == serenity::ConnectionStage::Connected);
+        let mut databases = serde_json::Map::new();
+        let database = &config::get().database;
+        for dataset in self.datasets.all() {
+            databases.insert(
+                dataset.name.clone(),
+                ready(dataset.snapshot(), "./src/lifecycle/queries/ready_dataset.sql").into(),
+            );
+        }
+        databases.insert(
+            "settings".to_owned(),
+            ready(&database.settings, "./src/lifecycle/queries/ready_migrated.sql").into(),
+        );
+        databases.insert(
+            "analytics".to_owned(),
+            ready(&database.analytics, "./src/lifecycle/queries/ready_migrated.sql").into(),
+        );
+        let shutting_down = self.shutting_down.load(Ordering::SeqCst);
+        let healthy = connected && !shutting_down && databases.values().all(|ready| ready == true);
+        (
+            healthy,
+            serde_json::json!({
+                "healthy": healthy,
+                "shutting_down": shutting_down,
+                "in_flight": self.in_flight.load(Ordering::SeqCst),
+                "gateway": {
+                    "connected": connected,
+                    "shards": shards.iter().map(|(id, stage)| serde_json::json!({
+                        "id": id,
+                        "stage": stage.to_string(),
+                    })).collect::<Vec<_>>(),
+                },
+                "databases": databases,
+            }),
+        )
+    }
+}
+
+/// Whether a database exists and the query at `path` returns a row from it.
+/// Opened read-only, so a missing file is reported rather than created empty.
+fn ready(database: &Path, path: &str) -> bool {
+    let result = rusqlite::Connection::open_with_flags(database, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.prepare(&read_query(path))?.exists([]));
+    match result {
+        Ok(ready) => ready,
+        Err(e) => {
+            tracing::warn!(database = %database.display(), error = %e, "Database is not ready");
+            false
+        }
+    }
+}
+
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Wraps a framework to count the events it handles as in flight, and turn commands away while shutting down.
+pub struct Tracked<F> {
+    pub framework: F,
+    pub lifecycle: Arc<Lifecycle>,
+}
+
+#[serenity::async_trait]
+impl<F: serenity::Framework> serenity::Framework for Tracked<F> {
+    async fn init(&mut self, client: &serenity::Client) {
+        self.framework.init(client).await
+    }
+
+    async fn dispatch(&self, ctx: serenity::Context, event: serenity::FullEvent) {
+        let Some(_in_flight) = self.lifecycle.begin() else {
+            if let serenity::FullEvent::InteractionCreate {
+                interaction: serenity::Interaction::Command(interaction),
+            } = &event
+            {
+                let _ = interaction
+                    .create_response(
+                        &ctx,
+                        serenity::CreateInteractionResponse::Message(
+                            serenity::CreateInteractionResponseMessage::new()
+                                .ephemeral(true)
+                                .content(SHUTTING_DOWN_MESSAGE),
+                        ),
+                    )
+                    .await;
+            }
+            return;
+        };
+        self.framework.dispatch(ctx, event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_or_missing_databases_are_not_ready() {
+        let dir = std::env::temp_dir().join(format!("lifecycle-ready-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing.db");
+        assert!(!ready(&missing, "./src/lifecycle/queries/ready_migrated.sql"));
+        assert!(!missing.exists());
+
+        let empty = dir.join("empty.db");
+        rusqlite::Connection::open(&empty).unwrap();
+        assert!(!ready(&empty, "./src/lifecycle/queries/ready_dataset.sql"));
+        assert!(!ready(&empty, "./src/lifecycle/queries/ready_migrated.sql"));
+
+        assert!(ready(Path::new("rowedex.db"), "./src/lifecycle/queries/ready_dataset.sql"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
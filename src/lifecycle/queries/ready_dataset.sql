@@ -0,0 +1,7 @@
+-- The dataset snapshot has Pokemon in it.
+select
+    1
+from
+    pokemon
+limit
+    1
@@ -0,0 +1,7 @@
+-- The database has been migrated.
+select
+    1
+from
+    schema_version
+limit
+    1
@@ -0,0 +1,58 @@
+//! Structured logging through `tracing`.
+//!
+//! `logging.level` takes `tracing_subscriber::EnvFilter` directives (e.g. `debug` or `info,hammer_plus::calc=trace`),
+//! and `logging.format = "json"` switches to one JSON object per line. Logs go to stderr so dexctl's reports stay clean.
+//!
+//! Every message and interaction is handled inside a `dispatch` span carrying its guild, channel and user,
+//! and commands record their name into it, so everything logged while handling a command can be traced back to it.
+
+use poise::serenity_prelude as serenity;
+use std::io::IsTerminal;
+use tracing::field::Empty;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+use crate::config::{LogFormat, Logging};
+use crate::{Data, Error};
+
+pub fn init(config: &Logging) {
+    let builder = tracing_subscriber::fmt()
+        // Checked when the configuration is loaded.
+        .with_env_filter(EnvFilter::new(&config.level))
+        .with_writer(std::io::stderr)
+        // No color codes in log files.
+        .with_ansi(std::io::stderr().is_terminal());
+    match config.format {
+        LogFormat::Json => builder.json().with_current_span(true).init(),
+        LogFormat::Text => builder.init(),
+    }
+}
+
+/// Wraps poise's framework to handle each message and interaction inside a span.
+pub struct TracedFramework(pub poise::Framework<Data, Error>);
+
+#[serenity::async_trait]
+impl serenity::Framework for TracedFramework {
+    async fn init(&mut self, client: &serenity::Client) {
+        self.0.init(client).await
+    }
+
+    async fn dispatch(&self, ctx: serenity::Context, event: serenity::FullEvent) {
+        let (guild_id, channel_id, user_id) = match &event {
+            serenity::FullEvent::Message { new_message } => {
+                (new_message.guild_id, new_message.channel_id, new_message.author.id)
+            }
+            serenity::FullEvent::InteractionCreate {
+                interaction: serenity::Interaction::Command(interaction) | serenity::Interaction::Autocomplete(interaction),
+            } => (interaction.guild_id, interaction.channel_id, interaction.user.id),
+            _ => return self.0.dispatch(ctx, event).await,
+        };
+        let span = tracing::info_span!(
+            "dispatch",
+            guild_id = guild_id.map(|guild_id| guild_id.get()),
+            channel_id = channel_id.get(),
+            user_id = user_id.get(),
+            command = Empty,
+        );
+        self.0.dispatch(ctx, event).instrument(span).await
+    }
+}
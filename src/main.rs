@@ -1,15 +1,43 @@
 #![warn(clippy::str_to_string)]
 
+mod admin;
+mod analytics;
+mod calc;
+mod card;
+mod compare;
+mod config;
+mod datasets;
 mod dex;
+mod dexctl;
 mod help;
+mod http;
+mod lifecycle;
+mod migrations;
+mod permissions;
+mod pokemon_of_the_day;
+mod random;
+mod search;
+mod settings;
+mod speed;
+mod sprites;
+mod trivia;
+mod whos_that;
 mod event_controller;
+mod guild_config;
+mod logging;
+mod metrics;
 
 use poise::serenity_prelude as serenity;
-use std::{env::var, sync::Arc, time::Duration};
+use std::{sync::Arc, time::{Duration, Instant}};
+use datasets::Datasets;
 use event_controller::Handler;
+use lifecycle::Lifecycle;
 
 // Custom user data passed to all command functions
-pub struct Data {}
+pub struct Data {
+    pub datasets: Arc<Datasets>,
+    pub started: Instant,
+}
 
 // Types used by all command functions
 type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -24,11 +52,20 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
             panic!("Failed to start bot: {:?}", error)
         }
         poise::FrameworkError::Command { error, ctx, .. } => {
-            println!("Error in command `{}`: {:?}", ctx.command().name, error,);
+            let duration_ms = analytics::finish(ctx, false).await;
+            metrics::command(&ctx.command().qualified_name, false, duration_ms);
+            metrics::ERRORS.with_label_values(&["command"]).inc();
+            tracing::error!(
+                command = %ctx.command().qualified_name,
+                duration_ms,
+                error = ?error,
+                "Command failed"
+            );
         }
         error => {
+            metrics::ERRORS.with_label_values(&["framework"]).inc();
             if let Err(e) = poise::builtins::on_error(error).await {
-                println!("Error while handling error: {}", e)
+                tracing::error!(error = %e, "Error while handling error")
             }
         }
     }
@@ -36,19 +73,64 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
 
 #[tokio::main]
 async fn main() {
+    // `dexctl` maintains the database offline instead of starting the bot, and takes its own arguments.
+    let args: Vec<String> = std::env::args().collect();
+    let dexctl = args.get(1).map(String::as_str) == Some("dexctl");
+    let config = match config::init(if dexctl { &[] } else { &args[1..] }) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
+    logging::init(&config.logging);
+    if dexctl {
+        std::process::exit(dexctl::run(&args[2..]));
+    }
+    let token = match config.token() {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = migrations::migrate(&mut settings::open(), &settings::MIGRATIONS) {
+        panic!("Failed to migrate the settings database: {}", e)
+    }
+    if let Err(e) = migrations::migrate(&mut analytics::open(), &analytics::MIGRATIONS) {
+        panic!("Failed to migrate the analytics database: {}", e)
+    }
+    let datasets = match Datasets::load() {
+        Ok(datasets) => Arc::new(datasets),
+        Err(e) => panic!("Failed to load datasets: {}", e),
+    };
+    let owners = config.owners.iter().copied().map(serenity::UserId::new).collect();
+    // Commands are only registered in these guilds when set, so changes show up right away while testing.
+    let dev_guilds: Vec<serenity::GuildId> = config.dev_guilds.iter().copied().map(serenity::GuildId::new).collect();
+    let lifecycle = Arc::new(Lifecycle::new(datasets.clone()));
+    if config.features.hot_reload {
+        tokio::spawn(datasets::watch(datasets.clone()));
+    }
+    if let Some(addr) = config.http.addr {
+        tokio::spawn(http::serve(addr, lifecycle.clone()));
+    }
+
     // FrameworkOptions contains all of poise's configuration option in one struct
     // Every option can be omitted to use its default value
     let options = poise::FrameworkOptions {
-        commands: vec![help::help(), dex::dex()],
+        commands: vec![help::help(), dex::dex(), search::search(), dex::stats::stats(), calc::calc(), speed::speed(), compare::compare(), random::random(), datasets::dataset(), admin::admin(), guild_config::config(), whos_that::whos_that(), trivia::trivia()],
         prefix_options: poise::PrefixFrameworkOptions {
-            prefix: Some("~".into()),
-            edit_tracker: Some(Arc::new(poise::EditTracker::for_timespan(
-                Duration::from_secs(3600),
-            ))),
-            additional_prefixes: vec![
-                poise::Prefix::Literal("hey bot"),
-                poise::Prefix::Literal("hey bot,"),
-            ],
+            // Each guild's own prefix, or the configured one
+            dynamic_prefix: Some(|ctx| Box::pin(guild_config::dynamic_prefix(ctx))),
+            edit_tracker: config.features.edit_tracking.then(|| {
+                Arc::new(poise::EditTracker::for_timespan(Duration::from_secs(3600)))
+            }),
+            additional_prefixes: config
+                .additional_prefixes
+                .iter()
+                .map(|prefix| poise::Prefix::Literal(prefix))
+                .collect(),
             ..Default::default()
         },
         // The global error handler for all error cases that may occur
@@ -56,58 +138,79 @@ async fn main() {
         // This code is run before every command
         pre_command: |ctx| {
             Box::pin(async move {
-                println!(
-                    "Executing command {}...",
-                    ctx.command().qualified_name
-                );
+                tracing::Span::current().record("command", ctx.command().qualified_name.as_str());
+                analytics::start(ctx).await;
+                tracing::debug!("Executing command");
             })
         },
         // This code is run after a command if it was successful (returned Ok)
         post_command: |ctx| {
             Box::pin(async move {
-                println!("Executed command {}!", ctx.command().qualified_name);
+                let duration_ms = analytics::finish(ctx, true).await;
+                metrics::command(&ctx.command().qualified_name, true, duration_ms);
+                tracing::info!(duration_ms, "Executed command");
             })
         },
         // Every command invocation must pass this check to continue execution
-        command_check: Some(|ctx| {
-            Box::pin(async move {
-                if ctx.author().id == 123456789 {
-                    return Ok(false);
-                }
-                Ok(true)
-            })
-        }),
+        command_check: Some(|ctx| Box::pin(permissions::command_check(ctx))),
         // Enforce command checks even for owners (enforced by default)
         // Set to true to bypass checks, which is useful for testing
         skip_checks_for_owners: false,
+        // The application's owner is added to these at startup
+        owners,
         ..Default::default()
     };
 
+    let data_datasets = datasets.clone();
+    let potd_datasets = datasets.clone();
     let framework = poise::Framework::builder()
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
-                println!("Logged in as {}", _ready.user.name);
-                poise::builtins::register_globally(
-                    ctx,
-                    &framework.options().commands,
-                )
-                .await?;
-                Ok(Data {})
+                tracing::info!(user = %_ready.user.name, "Logged in");
+                if dev_guilds.is_empty() {
+                    poise::builtins::register_globally(
+                        ctx,
+                        &framework.options().commands,
+                    )
+                    .await?;
+                } else {
+                    for guild_id in &dev_guilds {
+                        poise::builtins::register_in_guild(
+                            ctx,
+                            &framework.options().commands,
+                            *guild_id,
+                        )
+                        .await?;
+                    }
+                    tracing::info!(guilds = ?dev_guilds, "Registered commands in dev guilds only");
+                }
+                Ok(Data {
+                    datasets: data_datasets,
+                    started: Instant::now(),
+                })
             })
         })
         .options(options)
         .build();
 
-    let token = var("DISCORD_TOKEN").expect(
-        "Missing `DISCORD_TOKEN` env var, see README for more information.",
-    );
     let intents = serenity::GatewayIntents::non_privileged()
         | serenity::GatewayIntents::MESSAGE_CONTENT;
 
-    let client = serenity::ClientBuilder::new(token, intents)
-        .framework(framework)
-        .event_handler(Handler)
-        .await;
+    let mut client = serenity::ClientBuilder::new(token, intents)
+        .framework(lifecycle::Tracked {
+            framework: logging::TracedFramework(framework),
+            lifecycle: lifecycle.clone(),
+        })
+        .event_handler(Handler {
+            datasets,
+            lifecycle: lifecycle.clone(),
+        })
+        .await
+        .unwrap();
 
-    client.unwrap().start().await.unwrap()
+    lifecycle.set_shard_manager(client.shard_manager.clone());
+    tokio::spawn(pokemon_of_the_day::schedule(client.http.clone(), potd_datasets));
+    tokio::spawn(lifecycle.shutdown_on_signal());
+    client.start().await.unwrap();
+    tracing::info!("Shut down");
 }
@@ -0,0 +1,93 @@
+//! Prometheus metrics, served at `/metrics` by the http module.
+
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_counter_vec, Encoder, HistogramTimer, HistogramVec,
+    IntCounter, IntCounterVec, TextEncoder,
+};
+use std::path::Path;
+use std::sync::LazyLock;
+
+pub static COMMANDS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("rowedex_commands_total", "Commands executed, by command and outcome.", &["command", "outcome"])
+        .unwrap()
+});
+
+pub static COMMAND_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!("rowedex_command_duration_seconds", "Time taken to run commands.", &["command"]).unwrap()
+});
+
+pub static COMPONENTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "rowedex_component_interactions_total",
+        "Dex button presses, by button and outcome.",
+        &["action", "outcome"]
+    )
+    .unwrap()
+});
+
+pub static QUERY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "rowedex_query_duration_seconds",
+        "Time taken to run SQLite queries, by query file.",
+        &["query"],
+        vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
+    )
+    .unwrap()
+});
+
+pub static AUTOCOMPLETE_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "rowedex_autocomplete_duration_seconds",
+        "Time taken to answer autocomplete requests, by what was autocompleted.",
+        &["kind"],
+        vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
+    )
+    .unwrap()
+});
+
+pub static GATEWAY_RECONNECTS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("rowedex_gateway_reconnects_total", "Times a shard lost its gateway connection.").unwrap()
+});
+
+pub static ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("rowedex_errors_total", "Errors, by where they happened.", &["kind"]).unwrap()
+});
+
+/// Records a finished command.
+pub fn command(name: &str, success: bool, duration_ms: Option<u64>) {
+    COMMANDS.with_label_values(&[name, outcome(success)]).inc();
+    if let Some(duration_ms) = duration_ms {
+        COMMAND_DURATION
+            .with_label_values(&[name])
+            .observe(duration_ms as f64 / 1000.0);
+    }
+}
+
+/// Records a dex button press.
+pub fn component(action: &str, success: bool) {
+    COMPONENTS.with_label_values(&[action, outcome(success)]).inc();
+}
+
+/// Times a query until the returned timer is dropped, labelled with the query's file name.
+pub fn time_query(path: &str) -> HistogramTimer {
+    let name = Path::new(path)
+        .file_name()
+        .map_or(path.into(), |name| name.to_string_lossy());
+    QUERY_DURATION.with_label_values(&[&name]).start_timer()
+}
+
+fn outcome(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "error"
+    }
+}
+
+/// Every metric in the Prometheus text format, and its content type.
+pub fn encode() -> Result<(Vec<u8>, String), prometheus::Error> {
+    let mut body = Vec::new();
+    let encoder = TextEncoder::new();
+    encoder.encode(&prometheus::gather(), &mut body)?;
+    Ok((body, encoder.format_type().to_owned()))
+}
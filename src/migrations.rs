@@ -0,0 +1,157 @@
+//! Schema migrations for rowedex.db and the bot's own settings database.
+//!
+//! Each migration is a SQL file embedded into the binary and applied in order.
+//! The versions applied so far are recorded in the schema_version table.
+//! To change a schema, add a new file and append it to its migration list. Never edit one that has shipped.
+
+use rusqlite::{Connection, OptionalExtension};
+use crate::Error;
+
+pub struct Migrations {
+    /// (version, description, sql), in the order they are applied.
+    pub versions: &'static [(u32, &'static str, &'static str)],
+    /// A table that only exists in databases created before migrations were introduced.
+    /// Such databases are recorded as being at the first version without running it.
+    pub baseline_table: Option<&'static str>,
+}
+
+impl Migrations {
+    /// Newest schema version this build knows how to use.
+    pub fn latest(&self) -> u32 {
+        self.versions.last().map_or(0, |(version, _, _)| *version)
+    }
+}
+
+/// Migrations of the Pokemon databases (rowedex.db and the other datasets).
+pub const DEX: Migrations = Migrations {
+    versions: &[(1, "Initial schema", include_str!("migrations/0001_initial_schema.sql"))],
+    baseline_table: Some("pokemon"),
+};
+
+/// Brings the database up to the latest version and returns the version it was at before.
+///
+/// Fails without touching the database if it is newer than this build supports.
+pub fn migrate(conn: &mut Connection, migrations: &Migrations) -> Result<u32, Error> {
+    conn.execute(
+        "create table if not exists schema_version (version integer primary key not null, description text not null, applied_at text not null default current_timestamp)",
+        [],
+    )?;
+    let mut version = current_version(conn)?;
+    if version > migrations.latest() {
+        return Err(format!(
+            "The database is at schema version {}, but this build only supports up to version {}. Update the bot before using this database.",
+            version,
+            migrations.latest()
+        )
+        .into());
+    }
+    let original = version;
+
+    // Databases from before migrations existed already have the initial schema.
+    if let (0, Some(table), Some((baseline, description, _))) =
+        (version, migrations.baseline_table, migrations.versions.first())
+    {
+        if has_table(conn, table)? {
+            record(conn, *baseline, description)?;
+            version = *baseline;
+        }
+    }
+
+    for &(migration, description, sql) in migrations.versions {
+        if migration <= version {
+            continue;
+        }
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)
+            .map_err(|e| format!("Migration {} ({}) failed: {}", migration, description, e))?;
+        record(&tx, migration, description)?;
+        tx.commit()?;
+        tracing::info!(database = conn.path().unwrap_or(""), version = migration, description, "Migrated schema");
+    }
+    Ok(original)
+}
+
+/// Highest version recorded in schema_version, or 0 for a database that has never been migrated.
+pub fn current_version(conn: &Connection) -> Result<u32, rusqlite::Error> {
+    if !has_table(conn, "schema_version")? {
+        return Ok(0);
+    }
+    conn.query_row("select max(version) from schema_version", [], |row| {
+        row.get::<_, Option<u32>>(0)
+    })
+    .map(|version| version.unwrap_or(0))
+}
+
+fn has_table(conn: &Connection, name: &str) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        "select 1 from sqlite_master where type = 'table' and name = ?1",
+        [name],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|table| table.is_some())
+}
+
+fn record(conn: &Connection, version: u32, description: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "insert into schema_version (version, description) values (?1, ?2)",
+        rusqlite::params![version, description],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THINGS: Migrations = Migrations {
+        versions: &[
+            (1, "Things", "create table things (id integer primary key not null);"),
+            (2, "Thing names", "alter table things add column name text;"),
+        ],
+        baseline_table: Some("things"),
+    };
+
+    #[test]
+    fn migrates_a_fresh_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&mut conn, &THINGS).unwrap(), 0);
+        assert_eq!(current_version(&conn).unwrap(), 2);
+        conn.execute("insert into things (id, name) values (1, 'Bulbasaur')", []).unwrap();
+    }
+
+    #[test]
+    fn records_the_baseline_without_running_it() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute("create table things (id integer primary key not null)", []).unwrap();
+        assert_eq!(migrate(&mut conn, &THINGS).unwrap(), 0);
+        assert_eq!(current_version(&conn).unwrap(), 2);
+        let versions: Vec<u32> = conn
+            .prepare("select version from schema_version order by version")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(versions, [1, 2]);
+    }
+
+    #[test]
+    fn running_again_changes_nothing() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, &THINGS).unwrap();
+        assert_eq!(migrate(&mut conn, &THINGS).unwrap(), 2);
+        let applied: u32 = conn.query_row("select count(*) from schema_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(applied, 2);
+    }
+
+    #[test]
+    fn refuses_a_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, &THINGS).unwrap();
+        record(&conn, 3, "From a newer build").unwrap();
+        let error = migrate(&mut conn, &THINGS).unwrap_err();
+        assert!(error.to_string().starts_with("The database is at schema version 3, but this build only supports up to version 2."));
+        assert_eq!(current_version(&conn).unwrap(), 3);
+    }
+}
@@ -0,0 +1,24 @@
+-- The schema rowedex.db shipped with before migrations were introduced.
+-- Databases created before then are recorded as being at this version without running it.
+create table if not exists flags (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail);
+create table if not exists egg_learnsets (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, pokemon integer references pokemon (id) not null on conflict fail, move integer references moves (id) not null on conflict fail);
+create table if not exists levelup_learnsets (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, pokemon integer references pokemon (id) not null, move integer references moves (id) not null, level tinyint not null);
+create table if not exists tmhm_learnsets (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, pokemon integer references pokemon (id) not null, move integer references moves (id) not null);
+create table if not exists tutor_learnsets (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, pokemon integer references pokemon (id) not null on conflict fail, move integer references moves (id) not null on conflict fail);
+create table if not exists locations (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, name text not null on conflict fail);
+create table if not exists abilities (id integer primary key on conflict rollback autoincrement unique, name text not null, internal_name text not null on conflict fail, description text not null);
+create table if not exists pokemon_ability_relationships (id integer primary key on conflict fail autoincrement unique on conflict fail, pokemon integer references pokemon (id) not null on conflict fail, ability integer references abilities (id) not null on conflict fail);
+create table if not exists egg_groups (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail, internal_name text not null on conflict fail);
+create table if not exists items (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail, internal_name text not null on conflict fail);
+create table if not exists effects (id integer primary key on conflict fail autoincrement unique on conflict fail, name text, internal_name text not null on conflict fail);
+create table if not exists targets (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail, internal_name text not null on conflict fail);
+create table if not exists splits (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail, internal_name text not null on conflict fail);
+create table if not exists moves (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null, internal_name text not null on conflict fail, type integer not null references types (id), split integer not null references splits (id), power tinyint, accuracy tinyint, pp tinyint, effect integer not null references effects (id), chance tinyint, description text, target integer references targets (id), priority tinyint);
+create table if not exists encounter_methods (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail);
+create table if not exists base_types (id integer primary key on conflict fail autoincrement unique on conflict fail, base_type integer references types (id) not null on conflict fail, type integer not null on conflict fail references types (id), effectiveness tinyint not null on conflict fail);
+create table if not exists types (id integer primary key on conflict fail autoincrement unique on conflict fail, name text not null on conflict fail, internal_name text not null on conflict fail);
+create table if not exists move_flag_relationships (id integer primary key on conflict fail autoincrement unique on conflict fail, move integer references moves (id) not null on conflict fail, flag integer references flags (id) not null on conflict fail);
+create table if not exists sightings (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, location integer references locations (id) not null on conflict fail, encounter_method integer references encounter_methods (id) not null on conflict fail, pokemon integer references pokemon (id) not null on conflict fail, chance integer constraint chance_percent_range check (chance > 0 and chance <= 100) not null on conflict fail);
+create table if not exists evolution_methods (id integer primary key on conflict fail autoincrement unique on conflict fail, name not null on conflict fail, internal_name not null on conflict fail);
+create table if not exists pokemon (id integer primary key on conflict fail autoincrement unique on conflict fail not null on conflict fail, pokedex_id integer, name text not null, internal_name text, base_hp smallint not null, base_atk smallint not null, base_def smallint not null, base_spa smallint not null, base_spd smallint not null, base_spe smallint not null, base_total smallint not null constraint base_total_calc generated always as (base_hp + base_atk + base_def + base_spa + base_spd + base_spe) stored, type1 integer references types (id), type2 integer references types (id), egg_group1 integer references egg_groups (id), egg_group2 integer references egg_groups (id), item1 integer references items (id), item2 integer references items (id), sprite blob);
+create table if not exists pokemon_evolution_relationships (id integer primary key on conflict fail autoincrement unique on conflict fail, pokemon integer references pokemon (id) not null on conflict fail, evolved integer references pokemon (id) not null on conflict fail, evolution_method integer references evolution_methods (id) not null on conflict fail, total smallint, item integer references items (id), move integer references moves (id), location integer references locations (id), type integer references types (id), pokemon_other integer references pokemon (id));
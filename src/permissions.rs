@@ -0,0 +1,62 @@
+//! Who may use the bot and who may manage it.
+//!
+//! Bot owners (the application's owner plus the ids in `BOT_OWNERS`) may do anything. In a guild, members with
+//! Manage Server or one of the roles added with `/admin admin-role` are its admins. Users and channels on the
+//! denylist can't use the bot at all, except for the `/admin` commands in a blocked channel so it can be allowed again.
+
+use crate::{settings, Context, Error};
+
+pub fn is_owner(ctx: Context<'_>) -> bool {
+    ctx.framework().options().owners.contains(&ctx.author().id)
+}
+
+/// Whether the author is a bot owner, or has Manage Server or an admin role in the guild the command was used in.
+pub async fn is_guild_admin(ctx: Context<'_>) -> Result<bool, Error> {
+    if is_owner(ctx) {
+        return Ok(true);
+    }
+    let (Some(guild_id), Some(member)) = (ctx.guild_id(), ctx.author_member().await) else {
+        return Ok(false);
+    };
+    // Interactions carry the member's permissions, prefix commands have to compute them from the cache.
+    let permissions = member
+        .permissions
+        .or_else(|| ctx.guild().map(|guild| guild.member_permissions(&member)))
+        .unwrap_or_default();
+    if permissions.manage_guild() || permissions.administrator() {
+        return Ok(true);
+    }
+    let admin_roles = settings::get_admin_roles(guild_id)?;
+    Ok(member.roles.iter().any(|role| admin_roles.contains(role)))
+}
+
+/// Command check for commands only guild admins may use.
+pub async fn guild_admin(ctx: Context<'_>) -> Result<bool, Error> {
+    if is_guild_admin(ctx).await? {
+        return Ok(true);
+    }
+    crate::dex::reply_error(
+        ctx,
+        "Only members with Manage Server or one of this server's admin roles can do that.".to_owned(),
+    )
+    .await?;
+    Ok(false)
+}
+
+/// Check every command has to pass: the author and the channel must not be on the denylist.
+pub async fn command_check(ctx: Context<'_>) -> Result<bool, Error> {
+    if is_owner(ctx) {
+        return Ok(true);
+    }
+    let is_admin_command = ctx.command().qualified_name.split(' ').next() == Some("admin");
+    let blocked = settings::is_user_blocked(ctx.author().id)?
+        || (!is_admin_command && settings::is_channel_blocked(ctx.channel_id())?);
+    if !blocked {
+        return Ok(true);
+    }
+    // Slash commands have to be answered, prefix commands are ignored so blocked channels stay quiet.
+    if let poise::Context::Application(_) = ctx {
+        crate::dex::reply_error(ctx, "You can't use this bot here.".to_owned()).await?;
+    }
+    Ok(false)
+}
@@ -0,0 +1,124 @@
+//! Posts a random Pokemon with its abilities and locations once a day, at `pokemon_of_the_day.time`,
+//! in every guild that picked a channel for it with `/config potd`.
+
+use poise::serenity_prelude as serenity;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use crate::datasets::Datasets;
+use crate::dex::{self, Location};
+use crate::guild_config::GuildConfig;
+use crate::sprites::{self, Variant};
+use crate::{config, random, settings, Error};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Posts the Pokemon of the day in the guilds that have not had it yet once it is time. Runs until the bot stops.
+/// Guilds that missed it while the bot was down get it when it comes back, on the same day.
+pub async fn schedule(http: Arc<serenity::Http>, datasets: Arc<Datasets>) {
+    let post_at = config::get().pokemon_of_the_day.seconds().expect("the time is validated at startup");
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+        if now % SECONDS_PER_DAY < post_at {
+            continue;
+        }
+        let day = (now / SECONDS_PER_DAY) as i64;
+        let due = match settings::get_potd_due(day) {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to list the guilds due a Pokemon of the day");
+                continue;
+            }
+        };
+        for (guild_id, channel_id) in due {
+            // Marked first, so a channel the bot cannot post in is not retried every minute.
+            if let Err(e) = settings::set_guild_potd_day(guild_id, day) {
+                tracing::error!(guild_id = %guild_id, error = %e, "Failed to record the Pokemon of the day");
+                continue;
+            }
+            if let Err(e) = post(&http, &datasets, guild_id, channel_id).await {
+                tracing::warn!(guild_id = %guild_id, channel_id = %channel_id, error = %e, "Failed to post the Pokemon of the day");
+            }
+        }
+    }
+}
+
+/// Posts a random Pokemon from the channel's default dataset.
+async fn post(
+    http: &serenity::Http,
+    datasets: &Datasets,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+) -> Result<(), Error> {
+    let dataset = settings::get_dataset(Some(guild_id), channel_id)?
+        .and_then(|name| datasets.get(&name))
+        .unwrap_or_else(|| datasets.default());
+    let Some(pokemon_id) = random::random_pokemon(&dataset, &[])? else {
+        return Ok(());
+    };
+    let p = dex::get_pokemon_by_id(&dataset, &pokemon_id)?;
+    let config = GuildConfig::get(Some(guild_id))?;
+    let labels = config.language.labels();
+    let abilities = dex::get_abilities(&dataset, &p.id)?;
+    let locations = dex::get_locations(&dataset, &p.id)?;
+    let (sprite_url, attachment) = sprites::find(&dataset, p.sprite.as_deref(), Variant::Front).for_embed().await;
+
+    let types = match (&p.type1_name, &p.type2_name) {
+        (Some(type1), Some(type2)) if p.type1 != p.type2 => format!("{}, {}", type1, type2),
+        (Some(type1), _) => type1.clone(),
+        _ => labels.none.to_owned(),
+    };
+    let abilities = if abilities.is_empty() {
+        labels.none.to_owned()
+    } else {
+        abilities
+            .iter()
+            .map(|ability| format!("**{}**: {}", ability.name, ability.description))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let mut embed = serenity::CreateEmbed::new()
+        .title(format!("{}: #{} {}", labels.pokemon_of_the_day, p.pokedex_id.unwrap_or(0), p.name))
+        .colour(dex::get_color_from_type(p.type1_name.as_deref().unwrap_or("")))
+        .thumbnail(sprite_url)
+        .field(labels.types, types, true)
+        .field(labels.abilities, dex::truncate_lines(abilities), false)
+        .field(labels.locations, format_locations(&locations, labels.none), false);
+    if let Some(url) = dataset.external_link(p.internal_name.as_deref().unwrap_or("")) {
+        embed = embed.url(url);
+    }
+    channel_id
+        .send_message(
+            http,
+            serenity::CreateMessage::new()
+                .embed(embed)
+                .components(dex::components(&config, &dataset, &p, Variant::Front).await)
+                .add_file(attachment),
+        )
+        .await?;
+    tracing::info!(guild_id = %guild_id, dataset = %dataset.name, pokemon_id = p.id, "Posted the Pokemon of the day");
+    Ok(())
+}
+
+/// One line per location with its encounter methods and chances, e.g. "Route 101: Day 20%, Night 20%".
+fn format_locations(locations: &[Location], none: &str) -> String {
+    let mut lines: Vec<(&str, Vec<String>)> = Vec::new();
+    for location in locations {
+        let method = format!("{} {}%", location.method, location.chance);
+        match lines.iter_mut().find(|(name, _)| *name == location.name) {
+            Some((_, methods)) => methods.push(method),
+            None => lines.push((&location.name, vec![method])),
+        }
+    }
+    if lines.is_empty() {
+        return none.to_owned();
+    }
+    dex::truncate_lines(
+        lines
+            .into_iter()
+            .map(|(name, methods)| format!("{}: {}", name, methods.join(", ")))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    )
+}
@@ -0,0 +1,64 @@
+use crate::{Context, Error};
+use crate::datasets::{self, Dataset};
+use crate::dex::{self, autocomplete, reply_error, stats::{RankAmong, Stat}};
+use crate::search::query::{self, Comparison, Filter};
+
+/// Show a random Pokemon from the server PokeDex, optionally narrowed down by filters.
+///
+/// Usage:
+/// /random
+/// /random type:Dragon min_bst:500 fully_evolved:True
+#[poise::command(slash_command)]
+pub async fn random(
+    ctx: Context<'_>,
+    #[description = "Only Pokemon of this type."]
+    #[autocomplete = "autocomplete::autocomplete_type"]
+    #[rename = "type"]
+    type_name: Option<String>,
+    #[description = "Only Pokemon in this egg group."]
+    #[autocomplete = "autocomplete::autocomplete_egg_group"]
+    egg_group: Option<String>,
+    #[description = "Lowest base stat total."]
+    min_bst: Option<u16>,
+    #[description = "Highest base stat total."]
+    max_bst: Option<u16>,
+    #[description = "Only Pokemon that cannot evolve any further."]
+    fully_evolved: Option<bool>,
+    #[description = "Game dataset to pick from."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let mut filters = Vec::new();
+    filters.extend(type_name.map(Filter::Type));
+    filters.extend(egg_group.map(Filter::Egg));
+    filters.extend(min_bst.map(|bst| Filter::Stat(Stat::Bst, Comparison::Ge, bst)));
+    filters.extend(max_bst.map(|bst| Filter::Stat(Stat::Bst, Comparison::Le, bst)));
+    if fully_evolved == Some(true) {
+        filters.push(Filter::FullyEvolved);
+    }
+    let Some(pokemon_id) = random_pokemon(&dataset, &filters)? else {
+        return reply_error(ctx, "No Pokemon match these filters.".to_owned()).await;
+    };
+    let p = dex::get_pokemon_by_id(&dataset, &pokemon_id)?;
+    let rank_among = if fully_evolved == Some(true) { RankAmong::FullyEvolved } else { RankAmong::All };
+    dex::reply_pokemon(ctx, &dataset, p, rank_among).await
+}
+
+/// Id of a random Pokemon matching every filter, if any does.
+pub fn random_pokemon(dataset: &Dataset, filters: &[Filter]) -> Result<Option<u16>, rusqlite::Error> {
+    let base_sql = crate::dex::read_query("./src/random/queries/random_pokemon.sql");
+    let _timer = crate::metrics::time_query("./src/random/queries/random_pokemon.sql");
+    let (conditions, params) = query::compile(filters);
+    let sql = format!("{0}{1}order by\n    random()\nlimit 1", base_sql, conditions);
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
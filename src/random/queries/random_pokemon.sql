@@ -0,0 +1,7 @@
+select
+    p.id
+from
+    pokemon p
+where
+    -- Placeholder rows such as the None pokemon have no stats.
+    p.base_total > 0
@@ -0,0 +1,197 @@
+pub mod query;
+
+use poise::serenity_prelude as serenity;
+use crate::{Context, Error};
+use crate::datasets::{self, Dataset};
+use crate::dex::{reply_error, stats::Stat};
+
+const RESULTS_PER_PAGE: usize = 15;
+
+struct SearchResult {
+    pokedex_id: Option<u16>,
+    name: String,
+    type1_name: Option<String>,
+    type2_name: Option<String>,
+    base_hp: u8,
+    base_atk: u8,
+    base_def: u8,
+    base_spa: u8,
+    base_spd: u8,
+    base_spe: u8,
+    base_total: u16,
+}
+
+impl SearchResult {
+    fn stat(&self, stat: Stat) -> u16 {
+        match stat {
+            Stat::Hp => self.base_hp.into(),
+            Stat::Atk => self.base_atk.into(),
+            Stat::Def => self.base_def.into(),
+            Stat::Spa => self.base_spa.into(),
+            Stat::Spd => self.base_spd.into(),
+            Stat::Spe => self.base_spe.into(),
+            Stat::Bst => self.base_total,
+        }
+    }
+
+    fn types(&self) -> String {
+        match (&self.type1_name, &self.type2_name) {
+            (Some(type1), Some(type2)) if type1 != type2 => format!("{}/{}", type1, type2),
+            (Some(type1), _) => type1.clone(),
+            _ => "None".to_owned(),
+        }
+    }
+}
+
+/// Search the server PokeDex using stat, type, ability, move and egg group filters.
+///
+/// Usage:
+/// /search type:fire spe>100 ability:"speed boost" learns:"swords dance" bst<500 egg:field
+///
+/// Stats (hp, atk, def, spa, spd, spe, bst) accept :, =, <, <=, > and >=.
+/// Words without a key are matched against the Pokemon's name.
+#[poise::command(slash_command)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "Filters, e.g. type:fire spe>100 ability:\"speed boost\" learns:\"swords dance\""]
+    query: String,
+    #[description = "Stat to sort the results by (defaults to BST)."]
+    sort: Option<Stat>,
+    #[description = "Sort from lowest to highest instead."]
+    ascending: Option<bool>,
+    #[description = "Game dataset to use."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let sort = sort.unwrap_or(Stat::Bst);
+    let filters = match query::parse(&query) {
+        Ok(filters) => filters,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let results = search_pokemon(&dataset, &filters, sort, ascending.unwrap_or(false))?;
+    if results.is_empty() {
+        return reply_error(ctx, format!("No Pokemon match `{}`.", query)).await;
+    }
+
+    let pages = results
+        .chunks(RESULTS_PER_PAGE)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|result| {
+                    format!(
+                        "`{0:>4}` #{1} {2} ({3})\n",
+                        result.stat(sort),
+                        result.pokedex_id.unwrap_or(0),
+                        result.name,
+                        result.types()
+                    )
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>();
+    let title = format!(
+        "{0} result{1} sorted by {2}",
+        results.len(),
+        if results.len() == 1 { "" } else { "s" },
+        sort.short_name()
+    );
+    paginate(ctx, &title, &pages).await?;
+    Ok(())
+}
+
+fn search_pokemon(
+    dataset: &Dataset,
+    filters: &[query::Filter],
+    sort: Stat,
+    ascending: bool,
+) -> Result<Vec<SearchResult>, rusqlite::Error> {
+    let base_sql = crate::dex::read_query("./src/search/queries/search_pokemon.sql");
+    let _timer = crate::metrics::time_query("./src/search/queries/search_pokemon.sql");
+    let (conditions, params) = query::compile(filters);
+    let sql = format!(
+        "{0}{1}order by\n    {2} {3}, p.pokedex_id asc",
+        base_sql,
+        conditions,
+        sort.column(),
+        if ascending { "asc" } else { "desc" }
+    );
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+        Ok(SearchResult {
+            pokedex_id: row.get(1)?,
+            name: row.get(2)?,
+            type1_name: row.get(3)?,
+            type2_name: row.get(4)?,
+            base_hp: row.get(5)?,
+            base_atk: row.get(6)?,
+            base_def: row.get(7)?,
+            base_spa: row.get(8)?,
+            base_spd: row.get(9)?,
+            base_spe: row.get(10)?,
+            base_total: row.get(11)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Sends an ephemeral embed with previous/next buttons that flip through the given pages.
+/// Based on poise::builtins::paginate, which cannot send ephemeral replies.
+pub async fn paginate(ctx: Context<'_>, title: &str, pages: &[String]) -> Result<(), Error> {
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{}prev", ctx_id);
+    let next_button_id = format!("{}next", ctx_id);
+    let page_embed = |page: usize| {
+        serenity::CreateEmbed::new()
+            .title(title)
+            .description(&pages[page])
+            .footer(serenity::CreateEmbedFooter::new(format!(
+                "Page {0}/{1}",
+                page + 1,
+                pages.len()
+            )))
+    };
+
+    let mut reply = poise::CreateReply::default()
+        .ephemeral(true)
+        .embed(page_embed(0));
+    if pages.len() > 1 {
+        reply = reply.components(vec![serenity::CreateActionRow::Buttons(vec![
+            serenity::CreateButton::new(&prev_button_id).emoji('◀'),
+            serenity::CreateButton::new(&next_button_id).emoji('▶'),
+        ])]);
+    }
+    ctx.send(reply).await?;
+    if pages.len() <= 1 {
+        return Ok(());
+    }
+
+    let mut current_page = 0;
+    while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(std::time::Duration::from_secs(3600))
+        .await
+    {
+        if press.data.custom_id == next_button_id {
+            current_page = (current_page + 1) % pages.len();
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(pages.len() - 1);
+        } else {
+            continue;
+        }
+        press
+            .create_response(
+                ctx.serenity_context(),
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new().embed(page_embed(current_page)),
+                ),
+            )
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+select 
+    p.id,
+    p.pokedex_id,
+    p.name,
+    t.name as type1_name,
+    t2.name as type2_name,
+    p.base_hp,
+    p.base_atk,
+    p.base_def,
+    p.base_spa,
+    p.base_spd,
+    p.base_spe,
+    p.base_total
+from 
+    pokemon p
+left join
+    types t on (t.id = p.type1)
+left join
+    types t2 on (t2.id = p.type2)
+where
+    -- Placeholder rows such as the None pokemon have no stats.
+    p.base_total > 0
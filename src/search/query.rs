@@ -0,0 +1,249 @@
+use rusqlite::types::Value;
+use crate::dex::stats::Stat;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    fn operator(&self) -> &'static str {
+        match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Eq => "=",
+            Comparison::Ge => ">=",
+            Comparison::Gt => ">",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Filter {
+    Name(String),
+    Type(String),
+    Ability(String),
+    Learns(String),
+    Egg(String),
+    Stat(Stat, Comparison, u16),
+    /// Pokemon that cannot evolve any further. Mega evolving does not count.
+    FullyEvolved,
+}
+
+/// Parses a search query such as
+/// `type:fire spe>100 ability:"speed boost" learns:"swords dance" bst<500 egg:field`.
+///
+/// Terms are separated by whitespace and all of them must match. Values containing
+/// spaces must be wrapped in double quotes. On failure, the returned message is meant
+/// to be shown to the end-user as is.
+pub fn parse(query: &str) -> Result<Vec<Filter>, String> {
+    tokenize(query)?
+        .iter()
+        .map(|token| parse_term(token))
+        .collect()
+}
+
+fn tokenize(query: &str) -> Result<Vec<String>, String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quote in search query.".to_owned());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+fn parse_term(token: &str) -> Result<Filter, String> {
+    let Some(split_at) = token.find([':', '<', '>', '=']) else {
+        // A bare word is treated as part of a Pokemon's name.
+        return Ok(Filter::Name(token.to_owned()));
+    };
+    let key = token[..split_at].to_lowercase();
+    let rest = &token[split_at..];
+    let (comparison, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Comparison::Ge, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Comparison::Le, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Comparison::Gt, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Comparison::Lt, value)
+    } else {
+        // Both `:` and `=` mean equality.
+        (Comparison::Eq, &rest[1..])
+    };
+    if value.is_empty() {
+        return Err(format!("Missing value for `{}`.", key));
+    }
+
+    if let Some(stat) = Stat::from_key(&key) {
+        let value = value
+            .parse::<u16>()
+            .map_err(|_| format!("`{}` is not a valid number for `{}`.", value, key))?;
+        return Ok(Filter::Stat(stat, comparison, value));
+    }
+    if comparison != Comparison::Eq {
+        return Err(format!("`{}` can only be compared with `:`.", key));
+    }
+    let value = value.to_owned();
+    match key.as_str() {
+        "name" => Ok(Filter::Name(value)),
+        "type" => Ok(Filter::Type(value)),
+        "ability" => Ok(Filter::Ability(value)),
+        "learns" | "move" => Ok(Filter::Learns(value)),
+        "egg" | "egggroup" => Ok(Filter::Egg(value)),
+        _ => Err(format!(
+            "Unknown search key `{}`. Try name, type, ability, learns, egg, hp, atk, def, spa, spd, spe or bst.",
+            key
+        )),
+    }
+}
+
+/// Compiles filters into conditions appended to the where clause of a query such as search_pokemon.sql,
+/// one "    and ..." line each. Every user supplied value is bound as a parameter, never spliced into the SQL.
+pub fn compile(filters: &[Filter]) -> (String, Vec<Value>) {
+    let mut conditions = String::new();
+    let mut params: Vec<Value> = Vec::new();
+    for filter in filters {
+        let condition = match filter {
+            Filter::Name(name) => {
+                params.push(Value::Text(format!("%{}%", name)));
+                "p.name like ?".to_owned()
+            }
+            Filter::Type(type_name) => {
+                params.push(Value::Text(type_name.clone()));
+                "exists (select 1 from types t where t.id in (p.type1, p.type2) and t.name like ?)"
+                    .to_owned()
+            }
+            Filter::Ability(ability) => {
+                params.push(Value::Text(ability.clone()));
+                "exists (select 1 from pokemon_ability_relationships pa \
+                    inner join abilities a on (a.id = pa.ability) \
+                    where pa.pokemon = p.id and a.name like ?)"
+                    .to_owned()
+            }
+            Filter::Learns(move_name) => {
+                params.push(Value::Text(move_name.clone()));
+                "exists (select 1 from moves m where m.name like ? and m.id in (\
+                    select move from levelup_learnsets where pokemon = p.id \
+                    union select move from tmhm_learnsets where pokemon = p.id \
+                    union select move from tutor_learnsets where pokemon = p.id \
+                    union select move from egg_learnsets where pokemon = p.id))"
+                    .to_owned()
+            }
+            Filter::Egg(egg_group) => {
+                params.push(Value::Text(egg_group.clone()));
+                "exists (select 1 from egg_groups e where e.id in (p.egg_group1, p.egg_group2) and e.name like ?)"
+                    .to_owned()
+            }
+            Filter::Stat(stat, comparison, value) => {
+                params.push(Value::Integer(i64::from(*value)));
+                format!("{} {} ?", stat.column(), comparison.operator())
+            }
+            Filter::FullyEvolved => "not exists (select 1 from pokemon_evolution_relationships per \
+                inner join evolution_methods em on (em.id = per.evolution_method) \
+                where per.pokemon = p.id and em.internal_name not in ('EVO_MEGA_EVOLUTION', 'EVO_MOVE_MEGA_EVOLUTION'))"
+                .to_owned(),
+        };
+        conditions.push_str(&format!("    and {}\n", condition));
+    }
+    (conditions, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_values() {
+        assert_eq!(
+            parse(r#"ability:"speed boost" learns:"swords dance" "mr mime""#).unwrap(),
+            [
+                Filter::Ability("speed boost".to_owned()),
+                Filter::Learns("swords dance".to_owned()),
+                Filter::Name("mr mime".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_comparisons() {
+        assert_eq!(
+            parse("spe>100 bst<=500 HP>=80 atk<90 def=70 spa:60").unwrap(),
+            [
+                Filter::Stat(Stat::Spe, Comparison::Gt, 100),
+                Filter::Stat(Stat::Bst, Comparison::Le, 500),
+                Filter::Stat(Stat::Hp, Comparison::Ge, 80),
+                Filter::Stat(Stat::Atk, Comparison::Lt, 90),
+                Filter::Stat(Stat::Def, Comparison::Eq, 70),
+                Filter::Stat(Stat::Spa, Comparison::Eq, 60),
+            ]
+        );
+        assert_eq!(
+            parse("  Type:fire   egggroup:field move:surf ").unwrap(),
+            [
+                Filter::Type("fire".to_owned()),
+                Filter::Egg("field".to_owned()),
+                Filter::Learns("surf".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(parse(r#"ability:"speed boost"#).unwrap_err(), "Unterminated quote in search query.");
+        assert_eq!(parse("type:").unwrap_err(), "Missing value for `type`.");
+        assert_eq!(parse("spe>fast").unwrap_err(), "`fast` is not a valid number for `spe`.");
+        assert_eq!(parse("bst>99999").unwrap_err(), "`99999` is not a valid number for `bst`.");
+        assert_eq!(parse("type>fire").unwrap_err(), "`type` can only be compared with `:`.");
+        assert!(parse("colour:red").unwrap_err().starts_with("Unknown search key `colour`."));
+    }
+
+    #[test]
+    fn compiles_conditions_and_params() {
+        let (conditions, params) = compile(&parse("char type:fire spe>=100").unwrap());
+        assert_eq!(
+            conditions,
+            [
+                "    and p.name like ?\n",
+                "    and exists (select 1 from types t where t.id in (p.type1, p.type2) and t.name like ?)\n",
+                "    and p.base_spe >= ?\n",
+            ]
+            .concat()
+        );
+        assert_eq!(
+            params,
+            [Value::Text("%char%".to_owned()), Value::Text("fire".to_owned()), Value::Integer(100)]
+        );
+    }
+
+    #[test]
+    fn compiles_nothing_without_filters() {
+        assert_eq!(compile(&[]), (String::new(), Vec::new()));
+    }
+
+    #[test]
+    fn never_splices_values_into_the_sql() {
+        let (conditions, params) = compile(&parse(r#"name:"'; drop table pokemon; --""#).unwrap());
+        assert_eq!(conditions, "    and p.name like ?\n");
+        assert_eq!(params, [Value::Text("%'; drop table pokemon; --%".to_owned())]);
+    }
+}
@@ -0,0 +1,359 @@
+//! The bot's own persistent state, such as each guild's and channel's choices, kept in settings.db
+//! so it survives replacing the Pokemon databases.
+
+use poise::serenity_prelude as serenity;
+use rusqlite::OptionalExtension;
+use crate::config;
+use crate::dex::read_query;
+use crate::migrations::Migrations;
+
+pub const MIGRATIONS: Migrations = Migrations {
+    versions: &[
+        (1, "Dataset defaults", include_str!("settings/migrations/0001_dataset_defaults.sql")),
+        (2, "Permissions", include_str!("settings/migrations/0002_permissions.sql")),
+        (3, "Guild config", include_str!("settings/migrations/0003_guild_config.sql")),
+        (4, "Dex cards", include_str!("settings/migrations/0004_dex_cards.sql")),
+        (5, "Pokemon of the day", include_str!("settings/migrations/0005_pokemon_of_the_day.sql")),
+        (6, "Who's that Pokemon", include_str!("settings/migrations/0006_whos_that.sql")),
+        (7, "Trivia", include_str!("settings/migrations/0007_trivia.sql")),
+    ],
+    baseline_table: None,
+};
+
+pub fn open() -> rusqlite::Connection {
+    rusqlite::Connection::open(&config::get().database.settings).unwrap()
+}
+
+/// The default dataset of a channel, or else of its guild.
+pub fn get_dataset(
+    guild_id: Option<serenity::GuildId>,
+    channel_id: serenity::ChannelId,
+) -> Result<Option<String>, rusqlite::Error> {
+    let conn = open();
+    let channel: Option<String> = conn
+        .query_row(
+            &read_query("./src/settings/queries/get_channel_dataset.sql"),
+            [channel_id.get() as i64],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    if channel.is_some() {
+        return Ok(channel);
+    }
+    let Some(guild_id) = guild_id else {
+        return Ok(None);
+    };
+    conn.query_row(
+        &read_query("./src/settings/queries/get_guild_dataset.sql"),
+        [guild_id.get() as i64],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+}
+
+pub fn set_guild_dataset(guild_id: serenity::GuildId, dataset: Option<&str>) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_dataset.sql", guild_id, dataset)
+}
+
+/// A guild's row of guild_settings, as edited with `/config`. See guild_config for what the values mean.
+#[derive(Default)]
+pub struct GuildSettings {
+    pub dataset: Option<String>,
+    pub prefix: Option<String>,
+    pub public_replies: bool,
+    /// Custom id prefixes of the dex buttons the guild turned off, separated by spaces.
+    pub hidden_buttons: String,
+    pub language: Option<String>,
+    /// Whether dex replies show a stat card image instead of embed fields.
+    pub dex_cards: bool,
+    /// Channel the Pokemon of the day is posted in, if any.
+    pub potd_channel: Option<serenity::ChannelId>,
+}
+
+pub fn get_guild_settings(guild_id: serenity::GuildId) -> Result<GuildSettings, rusqlite::Error> {
+    open()
+        .query_row(
+            &read_query("./src/settings/queries/get_guild_settings.sql"),
+            [guild_id.get() as i64],
+            |row| {
+                Ok(GuildSettings {
+                    dataset: row.get(0)?,
+                    prefix: row.get(1)?,
+                    public_replies: row.get(2)?,
+                    hidden_buttons: row.get(3)?,
+                    language: row.get(4)?,
+                    dex_cards: row.get(5)?,
+                    potd_channel: row.get::<_, Option<i64>>(6)?.map(|id| serenity::ChannelId::new(id as u64)),
+                })
+            },
+        )
+        .optional()
+        .map(Option::unwrap_or_default)
+}
+
+pub fn set_guild_prefix(guild_id: serenity::GuildId, prefix: Option<&str>) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_prefix.sql", guild_id, prefix)
+}
+
+pub fn set_guild_public_replies(guild_id: serenity::GuildId, public_replies: bool) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_public_replies.sql", guild_id, public_replies)
+}
+
+pub fn set_guild_dex_cards(guild_id: serenity::GuildId, dex_cards: bool) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_dex_cards.sql", guild_id, dex_cards)
+}
+
+pub fn set_guild_hidden_buttons(guild_id: serenity::GuildId, hidden_buttons: &str) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_hidden_buttons.sql", guild_id, hidden_buttons)
+}
+
+pub fn set_guild_language(guild_id: serenity::GuildId, language: Option<&str>) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_language.sql", guild_id, language)
+}
+
+pub fn set_guild_potd_channel(
+    guild_id: serenity::GuildId,
+    channel_id: Option<serenity::ChannelId>,
+) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_potd_channel.sql", guild_id, channel_id.map(|channel_id| channel_id.get() as i64))
+}
+
+/// Guilds with a Pokemon of the day channel that have not had a post on `day` yet.
+pub fn get_potd_due(day: i64) -> Result<Vec<(serenity::GuildId, serenity::ChannelId)>, rusqlite::Error> {
+    let conn = open();
+    let mut stmt = conn.prepare(&read_query("./src/settings/queries/get_potd_due.sql"))?;
+    let due = stmt
+        .query_map([day], |row| {
+            Ok((
+                serenity::GuildId::new(row.get::<_, i64>(0)? as u64),
+                serenity::ChannelId::new(row.get::<_, i64>(1)? as u64),
+            ))
+        })?
+        .collect();
+    due
+}
+
+pub fn set_guild_potd_day(guild_id: serenity::GuildId, day: i64) -> Result<(), rusqlite::Error> {
+    set_guild_setting("./src/settings/queries/set_guild_potd_day.sql", guild_id, day)
+}
+
+/// Runs one of the set_guild_*.sql upserts, which each write a single column of the guild's row.
+fn set_guild_setting(
+    path: &str,
+    guild_id: serenity::GuildId,
+    value: impl rusqlite::ToSql,
+) -> Result<(), rusqlite::Error> {
+    open().execute(&read_query(path), rusqlite::params![guild_id.get() as i64, value])?;
+    Ok(())
+}
+
+pub fn set_channel_dataset(channel_id: serenity::ChannelId, dataset: Option<&str>) -> Result<(), rusqlite::Error> {
+    open().execute(
+        &read_query("./src/settings/queries/set_channel_dataset.sql"),
+        rusqlite::params![channel_id.get() as i64, dataset],
+    )?;
+    Ok(())
+}
+
+/// The bot-wide default dataset set with `/admin default`, overriding the one in datasets.toml.
+pub fn get_bot_dataset() -> Result<Option<String>, rusqlite::Error> {
+    open()
+        .query_row(&read_query("./src/settings/queries/get_bot_dataset.sql"), [], |row| row.get(0))
+        .optional()
+        .map(Option::flatten)
+}
+
+pub fn set_bot_dataset(dataset: Option<&str>) -> Result<(), rusqlite::Error> {
+    open().execute(
+        &read_query("./src/settings/queries/set_bot_dataset.sql"),
+        [dataset],
+    )?;
+    Ok(())
+}
+
+/// Roles whose members may manage the bot in a guild, on top of members with Manage Server.
+pub fn get_admin_roles(guild_id: serenity::GuildId) -> Result<Vec<serenity::RoleId>, rusqlite::Error> {
+    let conn = open();
+    let mut stmt = conn.prepare(&read_query("./src/settings/queries/get_admin_roles.sql"))?;
+    let roles = stmt
+        .query_map([guild_id.get() as i64], |row| {
+            Ok(serenity::RoleId::new(row.get::<_, i64>(0)? as u64))
+        })?
+        .collect();
+    roles
+}
+
+/// Returns false if the role already was an admin role.
+pub fn add_admin_role(guild_id: serenity::GuildId, role_id: serenity::RoleId) -> Result<bool, rusqlite::Error> {
+    open()
+        .execute(
+            &read_query("./src/settings/queries/add_admin_role.sql"),
+            [guild_id.get() as i64, role_id.get() as i64],
+        )
+        .map(|changed| changed > 0)
+}
+
+/// Returns false if the role was not an admin role.
+pub fn remove_admin_role(guild_id: serenity::GuildId, role_id: serenity::RoleId) -> Result<bool, rusqlite::Error> {
+    open()
+        .execute(
+            &read_query("./src/settings/queries/remove_admin_role.sql"),
+            [guild_id.get() as i64, role_id.get() as i64],
+        )
+        .map(|changed| changed > 0)
+}
+
+/// Whether the user is on the denylist.
+pub fn is_user_blocked(user_id: serenity::UserId) -> Result<bool, rusqlite::Error> {
+    open().query_row(
+        &read_query("./src/settings/queries/is_user_blocked.sql"),
+        [user_id.get() as i64],
+        |row| row.get(0),
+    )
+}
+
+/// Whether the channel is on the denylist.
+pub fn is_channel_blocked(channel_id: serenity::ChannelId) -> Result<bool, rusqlite::Error> {
+    open().query_row(
+        &read_query("./src/settings/queries/is_channel_blocked.sql"),
+        [channel_id.get() as i64],
+        |row| row.get(0),
+    )
+}
+
+/// Returns false if the user already was blocked.
+pub fn block_user(user_id: serenity::UserId, reason: Option<&str>) -> Result<bool, rusqlite::Error> {
+    open()
+        .execute(
+            &read_query("./src/settings/queries/block_user.sql"),
+            rusqlite::params![user_id.get() as i64, reason],
+        )
+        .map(|changed| changed > 0)
+}
+
+/// Returns false if the user was not blocked.
+pub fn unblock_user(user_id: serenity::UserId) -> Result<bool, rusqlite::Error> {
+    open()
+        .execute(&read_query("./src/settings/queries/unblock_user.sql"), [user_id.get() as i64])
+        .map(|changed| changed > 0)
+}
+
+/// Returns false if the channel already was blocked.
+pub fn block_channel(guild_id: Option<serenity::GuildId>, channel_id: serenity::ChannelId) -> Result<bool, rusqlite::Error> {
+    open()
+        .execute(
+            &read_query("./src/settings/queries/block_channel.sql"),
+            rusqlite::params![channel_id.get() as i64, guild_id.map(|guild_id| guild_id.get() as i64)],
+        )
+        .map(|changed| changed > 0)
+}
+
+/// Returns false if the channel was not blocked.
+pub fn allow_channel(channel_id: serenity::ChannelId) -> Result<bool, rusqlite::Error> {
+    open()
+        .execute(&read_query("./src/settings/queries/allow_channel.sql"), [channel_id.get() as i64])
+        .map(|changed| changed > 0)
+}
+
+/// Number of blocked users and blocked channels.
+pub fn count_blocked() -> Result<(i64, i64), rusqlite::Error> {
+    open().query_row(
+        &read_query("./src/settings/queries/count_blocked.sql"),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// A player's "Who's that Pokemon?" record in a guild.
+pub struct WhosThatScore {
+    pub user_id: serenity::UserId,
+    pub wins: i64,
+    /// Rounds in a row the player won in the guild, up to the last one.
+    pub streak: i64,
+    pub best_streak: i64,
+}
+
+/// Records a round won, which ends everyone else's streak in the guild, and returns the winner's record.
+pub fn record_whos_that_win(guild_id: serenity::GuildId, user_id: serenity::UserId) -> Result<WhosThatScore, rusqlite::Error> {
+    let mut conn = open();
+    let tx = conn.transaction()?;
+    tx.execute(
+        &read_query("./src/settings/queries/end_other_whos_that_streaks.sql"),
+        [guild_id.get() as i64, user_id.get() as i64],
+    )?;
+    tx.execute(
+        &read_query("./src/settings/queries/record_whos_that_win.sql"),
+        [guild_id.get() as i64, user_id.get() as i64],
+    )?;
+    let score = tx.query_row(
+        &read_query("./src/settings/queries/get_whos_that_score.sql"),
+        [guild_id.get() as i64, user_id.get() as i64],
+        whos_that_score,
+    )?;
+    tx.commit()?;
+    Ok(score)
+}
+
+/// Ends every streak in the guild, when nobody won a round.
+pub fn end_whos_that_streaks(guild_id: serenity::GuildId) -> Result<(), rusqlite::Error> {
+    open().execute(&read_query("./src/settings/queries/end_whos_that_streaks.sql"), [guild_id.get() as i64])?;
+    Ok(())
+}
+
+/// The guild's players with the most wins.
+pub fn get_whos_that_leaderboard(guild_id: serenity::GuildId, limit: usize) -> Result<Vec<WhosThatScore>, rusqlite::Error> {
+    let conn = open();
+    let mut stmt = conn.prepare(&read_query("./src/settings/queries/get_whos_that_leaderboard.sql"))?;
+    let scores = stmt.query_map([guild_id.get() as i64, limit as i64], whos_that_score)?.collect();
+    scores
+}
+
+fn whos_that_score(row: &rusqlite::Row) -> Result<WhosThatScore, rusqlite::Error> {
+    Ok(WhosThatScore {
+        user_id: serenity::UserId::new(row.get::<_, i64>(0)? as u64),
+        wins: row.get(1)?,
+        streak: row.get(2)?,
+        best_streak: row.get(3)?,
+    })
+}
+
+/// A player's trivia record in a guild.
+pub struct TriviaScore {
+    pub user_id: serenity::UserId,
+    pub points: i64,
+    pub correct: i64,
+    pub answered: i64,
+}
+
+/// Records a player's answer to a trivia question, worth `points` if it was right.
+pub fn record_trivia_answer(
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+    points: i64,
+) -> Result<(), rusqlite::Error> {
+    let correct = i64::from(points > 0);
+    open().execute(
+        &read_query("./src/settings/queries/record_trivia_answer.sql"),
+        [guild_id.get() as i64, user_id.get() as i64, points, correct],
+    )?;
+    Ok(())
+}
+
+/// The guild's players with the most points.
+pub fn get_trivia_leaderboard(guild_id: serenity::GuildId, limit: usize) -> Result<Vec<TriviaScore>, rusqlite::Error> {
+    let conn = open();
+    let mut stmt = conn.prepare(&read_query("./src/settings/queries/get_trivia_leaderboard.sql"))?;
+    let scores = stmt
+        .query_map([guild_id.get() as i64, limit as i64], |row| {
+            Ok(TriviaScore {
+                user_id: serenity::UserId::new(row.get::<_, i64>(0)? as u64),
+                points: row.get(1)?,
+                correct: row.get(2)?,
+                answered: row.get(3)?,
+            })
+        })?
+        .collect();
+    scores
+}
@@ -0,0 +1,2 @@
+create table guild_settings (guild_id integer primary key not null, dataset text);
+create table channel_settings (channel_id integer primary key not null, dataset text);
@@ -0,0 +1,4 @@
+create table bot_settings (id integer primary key not null check (id = 1), dataset text);
+create table admin_roles (guild_id integer not null, role_id integer not null, primary key (guild_id, role_id));
+create table blocked_users (user_id integer primary key not null, reason text, blocked_at text not null default current_timestamp);
+create table blocked_channels (channel_id integer primary key not null, guild_id integer, blocked_at text not null default current_timestamp);
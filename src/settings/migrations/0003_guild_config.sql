@@ -0,0 +1,4 @@
+alter table guild_settings add column prefix text;
+alter table guild_settings add column public_replies integer not null default 0;
+alter table guild_settings add column hidden_buttons text not null default '';
+alter table guild_settings add column language text;
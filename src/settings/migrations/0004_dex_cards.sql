@@ -0,0 +1 @@
+alter table guild_settings add column dex_cards integer not null default 0;
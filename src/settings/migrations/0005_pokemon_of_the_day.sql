@@ -0,0 +1,3 @@
+alter table guild_settings add column potd_channel integer;
+-- Days since the Unix epoch, in UTC, of the last post.
+alter table guild_settings add column potd_day integer;
@@ -0,0 +1 @@
+create table whos_that_scores (guild_id integer not null, user_id integer not null, wins integer not null default 0, streak integer not null default 0, best_streak integer not null default 0, primary key (guild_id, user_id));
@@ -0,0 +1 @@
+create table trivia_scores (guild_id integer not null, user_id integer not null, points integer not null default 0, correct integer not null default 0, answered integer not null default 0, primary key (guild_id, user_id));
@@ -0,0 +1,4 @@
+insert or ignore into admin_roles
+    (guild_id, role_id)
+values
+    (?1, ?2)
@@ -0,0 +1,4 @@
+delete from
+    blocked_channels
+where
+    channel_id = ?1
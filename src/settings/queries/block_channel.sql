@@ -0,0 +1,4 @@
+insert or ignore into blocked_channels
+    (channel_id, guild_id)
+values
+    (?1, ?2)
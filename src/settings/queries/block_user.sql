@@ -0,0 +1,4 @@
+insert or ignore into blocked_users
+    (user_id, reason)
+values
+    (?1, ?2)
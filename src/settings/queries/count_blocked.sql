@@ -0,0 +1,3 @@
+select
+    (select count(*) from blocked_users),
+    (select count(*) from blocked_channels)
@@ -0,0 +1,7 @@
+update
+    whos_that_scores
+set
+    streak = 0
+where
+    guild_id = ?1
+    and user_id <> ?2
@@ -0,0 +1,6 @@
+update
+    whos_that_scores
+set
+    streak = 0
+where
+    guild_id = ?1
@@ -0,0 +1,6 @@
+select
+    role_id
+from
+    admin_roles
+where
+    guild_id = ?1
@@ -0,0 +1,6 @@
+select
+    dataset
+from
+    bot_settings
+where
+    id = 1
@@ -0,0 +1,6 @@
+select
+    dataset
+from
+    channel_settings
+where
+    channel_id = ?1
@@ -0,0 +1,6 @@
+select
+    dataset
+from
+    guild_settings
+where
+    guild_id = ?1
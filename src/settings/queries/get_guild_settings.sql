@@ -0,0 +1,12 @@
+select
+    dataset,
+    prefix,
+    public_replies,
+    hidden_buttons,
+    language,
+    dex_cards,
+    potd_channel
+from
+    guild_settings
+where
+    guild_id = ?1
@@ -0,0 +1,9 @@
+-- Guilds with a channel that have not had a post on day ?1 yet.
+select
+    guild_id,
+    potd_channel
+from
+    guild_settings
+where
+    potd_channel is not null
+    and (potd_day is null or potd_day < ?1)
@@ -0,0 +1,14 @@
+select
+    user_id,
+    points,
+    correct,
+    answered
+from
+    trivia_scores
+where
+    guild_id = ?1
+order by
+    points desc,
+    correct desc
+limit
+    ?2
@@ -0,0 +1,14 @@
+select
+    user_id,
+    wins,
+    streak,
+    best_streak
+from
+    whos_that_scores
+where
+    guild_id = ?1
+order by
+    wins desc,
+    best_streak desc
+limit
+    ?2
@@ -0,0 +1,10 @@
+select
+    user_id,
+    wins,
+    streak,
+    best_streak
+from
+    whos_that_scores
+where
+    guild_id = ?1
+    and user_id = ?2
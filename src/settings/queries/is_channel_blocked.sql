@@ -0,0 +1,7 @@
+select exists (
+    select 1
+    from
+        blocked_channels
+    where
+        channel_id = ?1
+)
@@ -0,0 +1,7 @@
+select exists (
+    select 1
+    from
+        blocked_users
+    where
+        user_id = ?1
+)
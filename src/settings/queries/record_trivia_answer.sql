@@ -0,0 +1,8 @@
+insert into trivia_scores
+    (guild_id, user_id, points, correct, answered)
+values
+    (?1, ?2, ?3, ?4, 1)
+on conflict (guild_id, user_id) do update set
+    points = points + ?3,
+    correct = correct + ?4,
+    answered = answered + 1
@@ -0,0 +1,8 @@
+insert into whos_that_scores
+    (guild_id, user_id, wins, streak, best_streak)
+values
+    (?1, ?2, 1, 1, 1)
+on conflict (guild_id, user_id) do update set
+    wins = wins + 1,
+    streak = streak + 1,
+    best_streak = max(best_streak, streak + 1)
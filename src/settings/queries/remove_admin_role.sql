@@ -0,0 +1,5 @@
+delete from
+    admin_roles
+where
+    guild_id = ?1
+    and role_id = ?2
@@ -0,0 +1,6 @@
+insert into bot_settings
+    (id, dataset)
+values
+    (1, ?1)
+on conflict (id) do update set
+    dataset = ?1
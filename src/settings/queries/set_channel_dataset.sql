@@ -0,0 +1,6 @@
+insert into channel_settings
+    (channel_id, dataset)
+values
+    (?1, ?2)
+on conflict (channel_id) do update set
+    dataset = ?2
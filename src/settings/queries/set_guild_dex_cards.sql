@@ -0,0 +1,6 @@
+insert into guild_settings
+    (guild_id, dex_cards)
+values
+    (?1, ?2)
+on conflict (guild_id) do update set
+    dex_cards = ?2
@@ -0,0 +1,6 @@
+insert into guild_settings
+    (guild_id, hidden_buttons)
+values
+    (?1, ?2)
+on conflict (guild_id) do update set
+    hidden_buttons = ?2
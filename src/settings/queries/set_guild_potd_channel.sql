@@ -0,0 +1,6 @@
+insert into guild_settings
+    (guild_id, potd_channel)
+values
+    (?1, ?2)
+on conflict (guild_id) do update set
+    potd_channel = ?2
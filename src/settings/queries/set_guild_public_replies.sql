@@ -0,0 +1,6 @@
+insert into guild_settings
+    (guild_id, public_replies)
+values
+    (?1, ?2)
+on conflict (guild_id) do update set
+    public_replies = ?2
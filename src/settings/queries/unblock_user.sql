@@ -0,0 +1,4 @@
+delete from
+    blocked_users
+where
+    user_id = ?1
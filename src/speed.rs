@@ -0,0 +1,232 @@
+use poise::serenity_prelude as serenity;
+use crate::{Context, Error};
+use crate::datasets;
+use crate::dex::{self, autocomplete, reply_error, truncate_list, Pokemon, FIELD_LIMIT};
+use crate::dex::stats::{calc_stat, get_base_stats, BaseStats, MAX_EV, MAX_IV};
+
+const TIERS_PER_PAGE: usize = 20;
+
+/// Common speed investments, from slowest to fastest.
+#[derive(Clone, Copy)]
+enum SpeedSpread {
+    Uninvested,
+    Max,
+    MaxPositive,
+    PlusOne,
+    PlusTwo,
+    Scarf,
+}
+
+impl SpeedSpread {
+    const ALL: [SpeedSpread; 6] = [
+        SpeedSpread::Uninvested,
+        SpeedSpread::Max,
+        SpeedSpread::MaxPositive,
+        SpeedSpread::PlusOne,
+        SpeedSpread::PlusTwo,
+        SpeedSpread::Scarf,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SpeedSpread::Uninvested => "0 Spe",
+            SpeedSpread::Max => "252 Spe",
+            SpeedSpread::MaxPositive => "252+ Spe",
+            SpeedSpread::PlusOne => "+1 252+ Spe",
+            SpeedSpread::PlusTwo => "+2 252+ Spe",
+            SpeedSpread::Scarf => "Scarf 252+ Spe",
+        }
+    }
+
+    fn speed(&self, base_spe: u8, level: u8) -> u16 {
+        let max_positive = calc_stat(base_spe, MAX_IV, MAX_EV, level, 110);
+        match self {
+            SpeedSpread::Uninvested => calc_stat(base_spe, MAX_IV, 0, level, 100),
+            SpeedSpread::Max => calc_stat(base_spe, MAX_IV, MAX_EV, level, 100),
+            SpeedSpread::MaxPositive => max_positive,
+            // +1 and Choice Scarf are both a 1.5x multiplier, rounded down.
+            SpeedSpread::PlusOne | SpeedSpread::Scarf => max_positive * 3 / 2,
+            SpeedSpread::PlusTwo => max_positive * 2,
+        }
+    }
+}
+
+/// Compare Pokemon's speed at common spreads against every fully evolved Pokemon.
+///
+/// Shows what share of fully evolved Pokemon each spread outspeeds, both when they run
+/// max speed (252+) and no speed investment.
+///
+/// Usage:
+/// /speed Garchomp
+/// /speed Garchomp pokemon2:Salamence tier_list:True
+#[poise::command(slash_command)]
+#[allow(clippy::too_many_arguments)]
+pub async fn speed(
+    ctx: Context<'_>,
+    #[description = "Pokemon to compare."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    pokemon: String,
+    #[description = "Another Pokemon to compare."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    pokemon2: Option<String>,
+    #[description = "Another Pokemon to compare."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    pokemon3: Option<String>,
+    #[description = "Another Pokemon to compare."]
+    #[autocomplete = "autocomplete::autocomplete_pokemon"]
+    pokemon4: Option<String>,
+    #[description = "Level of every Pokemon (defaults to 100)."]
+    #[min = 1]
+    #[max = 100]
+    level: Option<u8>,
+    #[description = "Also show the full speed tier list."]
+    tier_list: Option<bool>,
+    #[description = "Game dataset to use."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let level = level.unwrap_or(100);
+    let mut compared: Vec<Pokemon> = Vec::new();
+    for pokemon in [Some(pokemon), pokemon2, pokemon3, pokemon4].into_iter().flatten() {
+        match dex::resolve_pokemon(&dataset, &pokemon)? {
+            Some(p) => compared.push(p),
+            None => return reply_error(ctx, dex::unknown_pokemon(&pokemon)).await,
+        }
+    }
+    crate::analytics::target(ctx, &dataset, compared[0].id).await;
+    let fully_evolved = get_base_stats(&dataset, true)?;
+    let max_tiers = speed_tiers(&fully_evolved, SpeedSpread::MaxPositive, level);
+    let uninvested_tiers = speed_tiers(&fully_evolved, SpeedSpread::Uninvested, level);
+
+    let mut embed = serenity::CreateEmbed::new()
+        .title(format!("Speed tiers at level {}", level))
+        .colour(dex::get_color_from_type(
+            compared[0].type1_name.as_deref().unwrap_or(""),
+        ))
+        .footer(serenity::CreateEmbedFooter::new(format!(
+            "Compared against {} fully evolved Pokemon running 252+ Spe or 0 Spe.",
+            fully_evolved.len()
+        )));
+    for p in &compared {
+        let mut table = "```c\nSpread          Speed  >252+  >0\n".to_owned();
+        for spread in SpeedSpread::ALL {
+            let speed = spread.speed(p.base_spe, level);
+            let versus_max = format!("{:.0}%", outspeed_percent(&max_tiers, speed));
+            let versus_uninvested = format!("{:.0}%", outspeed_percent(&uninvested_tiers, speed));
+            table.push_str(&format!(
+                "{0:<15} {1:<6} {2:<6} {3}\n",
+                spread.label(),
+                speed,
+                versus_max,
+                versus_uninvested,
+            ));
+        }
+        table.push_str("```");
+        let neighbours = describe_neighbours(
+            &max_tiers,
+            SpeedSpread::MaxPositive.speed(p.base_spe, level),
+            &p.name,
+            FIELD_LIMIT.saturating_sub(table.len()),
+        );
+        table.push_str(&neighbours);
+        embed = embed.field(
+            format!("{0} (Base {1})", p.name, p.base_spe),
+            table,
+            false,
+        );
+    }
+    if compared.len() > 1 {
+        let mut order: Vec<(u16, &str)> = compared
+            .iter()
+            .map(|p| (SpeedSpread::MaxPositive.speed(p.base_spe, level), p.name.as_str()))
+            .collect();
+        order.sort_by_key(|(speed, _)| std::cmp::Reverse(*speed));
+        embed = embed.field(
+            "Fastest to slowest at 252+ Spe",
+            order
+                .iter()
+                .map(|(speed, name)| format!("{} ({})", name, speed))
+                .collect::<Vec<String>>()
+                .join(" > "),
+            false,
+        );
+    }
+    ctx.send(poise::CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+
+    if tier_list.unwrap_or(false) {
+        let pages = tier_list_pages(&max_tiers, &compared, level);
+        crate::search::paginate(ctx, "Fully evolved Pokemon at 252+ Spe", &pages).await?;
+    }
+    Ok(())
+}
+
+/// Speed of every Pokemon at the given spread, from fastest to slowest.
+fn speed_tiers(pokemon: &[BaseStats], spread: SpeedSpread, level: u8) -> Vec<(u16, &str)> {
+    let mut tiers: Vec<(u16, &str)> = pokemon
+        .iter()
+        .map(|p| (spread.speed(p.stats[5], level), p.name.as_str()))
+        .collect();
+    tiers.sort_by_key(|(speed, _)| std::cmp::Reverse(*speed));
+    tiers
+}
+
+fn outspeed_percent(tiers: &[(u16, &str)], speed: u16) -> f64 {
+    let slower = tiers.iter().filter(|(tier, _)| *tier < speed).count();
+    slower as f64 * 100.0 / tiers.len().max(1) as f64
+}
+
+/// Speed ties and the closest faster and slower Pokemon, in at most `limit` characters.
+fn describe_neighbours(tiers: &[(u16, &str)], speed: u16, name: &str, limit: usize) -> String {
+    let ties: Vec<&str> = tiers
+        .iter()
+        .filter(|(tier, tier_name)| *tier == speed && *tier_name != name)
+        .map(|(_, tier_name)| *tier_name)
+        .collect();
+    let faster = tiers.iter().rev().find(|(tier, _)| *tier > speed);
+    let slower = tiers.iter().find(|(tier, _)| *tier < speed);
+    let mut neighbours = String::new();
+    if let Some((tier, tier_name)) = faster {
+        neighbours.push_str(&format!("\nNext faster: {} ({})", tier_name, tier));
+    }
+    if let Some((tier, tier_name)) = slower {
+        neighbours.push_str(&format!("\nNext slower: {} ({})", tier_name, tier));
+    }
+    let mut output = format!("At 252+ Spe ({}):", speed);
+    if !ties.is_empty() {
+        // Common speeds are shared by dozens of Pokemon, so the ties get whatever room is left.
+        let prefix = "\nSpeed ties ";
+        let room = limit.saturating_sub(output.len() + prefix.len() + neighbours.len());
+        output.push_str(prefix);
+        output.push_str(&truncate_list(&ties, room));
+    }
+    output.push_str(&neighbours);
+    output
+}
+
+/// The fully evolved tier list, with every spread of the compared Pokemon slotted in.
+fn tier_list_pages(tiers: &[(u16, &str)], compared: &[Pokemon], level: u8) -> Vec<String> {
+    let mut lines: Vec<(u16, String)> = tiers
+        .iter()
+        .map(|(speed, name)| (*speed, format!("`{0:>4}` {1}\n", speed, name)))
+        .collect();
+    for p in compared {
+        for spread in SpeedSpread::ALL {
+            let speed = spread.speed(p.base_spe, level);
+            lines.push((
+                speed,
+                format!("**`{0:>4}` ► {1} ({2})**\n", speed, p.name, spread.label()),
+            ));
+        }
+    }
+    // Stable, so highlighted entries land after the Pokemon they tie with.
+    lines.sort_by_key(|(speed, _)| std::cmp::Reverse(*speed));
+    lines
+        .chunks(TIERS_PER_PAGE)
+        .map(|chunk| chunk.iter().map(|(_, line)| line.as_str()).collect::<String>())
+        .collect()
+}
@@ -0,0 +1,224 @@
+//! Pokemon sprites, in every variant the bot shows.
+//!
+//! Sprites imported with `dexctl sprites` are stored in `sprites.dir` as `<dataset>/<species>/<variant>.png`,
+//! where `<species>` is the folder of the sprite in the database relative to the dataset's `sprite_base`
+//! (e.g. `bulbasaur` or `question_mark/circled`), and served as attachments.
+//! Sprites that were not imported are downloaded once from `sprite_base` into `sprites.cache` and attached
+//! from there too, and a bundled question mark stands in for those that cannot be found at all.
+
+use poise::serenity_prelude as serenity;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use crate::config;
+use crate::datasets::Dataset;
+
+/// Shown when a Pokemon has no sprite, or it cannot be found.
+pub const MISSING: &[u8] = include_bytes!("../assets/sprites/missing.png");
+/// Name of sprite attachments, which embeds show with `attachment://`.
+pub const FILE_NAME: &str = "sprite.png";
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap()
+});
+
+/// Remote sprites that do not exist, so they are not asked for again until the bot restarts.
+/// Other download failures are retried.
+static NOT_FOUND: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+    Front,
+    Back,
+    ShinyFront,
+    ShinyBack,
+    Icon,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 5] = [
+        Variant::Front,
+        Variant::Back,
+        Variant::ShinyFront,
+        Variant::ShinyBack,
+        Variant::Icon,
+    ];
+
+    /// Variants the dex embed can switch between, in the order they are listed.
+    pub const SHOWN: [Variant; 4] = [Variant::Front, Variant::Back, Variant::ShinyFront, Variant::ShinyBack];
+
+    /// Value of the variant in the dex embed's sprite menu.
+    pub fn id(self) -> &'static str {
+        match self {
+            Variant::Front => "front",
+            Variant::Back => "back",
+            Variant::ShinyFront => "shiny_front",
+            Variant::ShinyBack => "shiny_back",
+            Variant::Icon => "icon",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Variant> {
+        Variant::ALL.into_iter().find(|variant| variant.id() == id)
+    }
+
+    /// File name in the store.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Variant::Front => "front.png",
+            Variant::Back => "back.png",
+            Variant::ShinyFront => "shiny_front.png",
+            Variant::ShinyBack => "shiny_back.png",
+            Variant::Icon => "icon.png",
+        }
+    }
+
+    /// File name in the decomp's graphics layout, which `sprite_base` points at.
+    /// Shiny sprites are only a palette there, so they can only be imported.
+    fn remote_file_name(self) -> Option<&'static str> {
+        match self {
+            Variant::Front => Some("front.png"),
+            Variant::Back => Some("back.png"),
+            Variant::Icon => Some("icon.png"),
+            Variant::ShinyFront | Variant::ShinyBack => None,
+        }
+    }
+}
+
+/// Where a sprite can be found.
+pub enum Sprite {
+    Stored(PathBuf),
+    Remote(String),
+    Missing,
+}
+
+/// Finds a variant of a Pokemon's sprite, given the `sprite` column of the database.
+pub fn find(dataset: &Dataset, sprite: Option<&str>, variant: Variant) -> Sprite {
+    let Some(sprite) = sprite.filter(|sprite| !sprite.is_empty()) else {
+        return Sprite::Missing;
+    };
+    if let Some(species) = species(dataset, sprite) {
+        let path = store_dir(&dataset.name).join(species).join(variant.file_name());
+        if path.is_file() {
+            return Sprite::Stored(path);
+        }
+    }
+    match variant.remote_file_name() {
+        Some(file_name) => {
+            let url = dataset.sprite_url(sprite);
+            match url.rsplit_once('/') {
+                Some((folder, _)) => Sprite::Remote(format!("{}/{}", folder, file_name)),
+                None => Sprite::Missing,
+            }
+        }
+        None => Sprite::Missing,
+    }
+}
+
+/// Variants of a Pokemon's sprite that can be found, among `variants`.
+/// Remote ones are downloaded into the cache to make sure they exist.
+pub async fn available(dataset: &Dataset, sprite: Option<&str>, variants: &[Variant]) -> Vec<Variant> {
+    let found = futures::future::join_all(variants.iter().map(|variant| async move {
+        match find(dataset, sprite, *variant) {
+            Sprite::Stored(_) => true,
+            Sprite::Remote(url) => fetch(&url).await.is_some(),
+            Sprite::Missing => false,
+        }
+    }))
+    .await;
+    variants
+        .iter()
+        .zip(found)
+        .filter_map(|(variant, found)| found.then_some(*variant))
+        .collect()
+}
+
+/// Folder of the store a dataset's sprites are imported into.
+pub fn store_dir(dataset: &str) -> PathBuf {
+    config::get().sprites.dir.join(dataset)
+}
+
+/// Folder of a sprite relative to the dataset's `sprite_base`, if it is under it.
+fn species<'a>(dataset: &Dataset, sprite: &'a str) -> Option<&'a str> {
+    let relative = if sprite.contains("://") {
+        sprite.strip_prefix(dataset.sprite_base.as_str())?
+    } else {
+        sprite
+    };
+    let (species, _) = relative.trim_start_matches('/').rsplit_once('/')?;
+    // Never look outside the store.
+    (!species.split('/').any(|part| part == ".." || part.is_empty())).then_some(species)
+}
+
+impl Sprite {
+    /// The sprite as an embed image URL, with the attachment it refers to.
+    pub async fn for_embed(self) -> (String, serenity::CreateAttachment) {
+        let attachment = serenity::CreateAttachment::bytes(self.png().await, FILE_NAME);
+        (format!("attachment://{}", FILE_NAME), attachment)
+    }
+
+    /// The sprite's PNG, downloading and caching it if it is remote, or the question mark if it cannot be found.
+    pub async fn png(self) -> Vec<u8> {
+        let png = match self {
+            Sprite::Stored(path) => match tokio::fs::read(&path).await {
+                Ok(png) => Some(png),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "Failed to read sprite");
+                    None
+                }
+            },
+            Sprite::Remote(url) => fetch(&url).await,
+            Sprite::Missing => None,
+        };
+        png.unwrap_or_else(|| MISSING.to_vec())
+    }
+}
+
+/// The PNG at `url`, from the cache or else downloaded into it. None if it could not be downloaded.
+async fn fetch(url: &str) -> Option<Vec<u8>> {
+    let path = cache_path(url);
+    if let Ok(png) = tokio::fs::read(&path).await {
+        return Some(png);
+    }
+    if NOT_FOUND.lock().unwrap().contains(url) {
+        return None;
+    }
+    let png = match download(url).await {
+        Ok(png) => png,
+        Err(e) => {
+            if e.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+                NOT_FOUND.lock().unwrap().insert(url.to_owned());
+            }
+            tracing::warn!(url, error = %e, "Failed to download sprite");
+            return None;
+        }
+    };
+    let written = async {
+        tokio::fs::create_dir_all(&config::get().sprites.cache).await?;
+        tokio::fs::write(&path, &png).await
+    };
+    if let Err(e) = written.await {
+        tracing::warn!(path = %path.display(), error = %e, "Failed to cache sprite");
+    }
+    Some(png)
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, reqwest::Error> {
+    let response = CLIENT.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Cache file of a URL, named after it, e.g. `raw.githubusercontent.com_..._bulbasaur_front.png`.
+fn cache_path(url: &str) -> PathBuf {
+    let name: String = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    config::get().sprites.cache.join(name)
+}
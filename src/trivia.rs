@@ -0,0 +1,428 @@
+//! Multiple choice trivia questions generated from the dataset's database, with points kept per guild.
+//!
+//! Difficulty follows how obscure the data is: types of fully evolved Pokemon are common knowledge, abilities
+//! and types of early stages take some play time, and level-up levels and night encounters take a guide.
+
+use futures::StreamExt;
+use poise::serenity_prelude as serenity;
+use poise::ChoiceParameter;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use crate::datasets::{self, Dataset};
+use crate::dex::{self, read_query, reply_error};
+use crate::search::query::Filter;
+use crate::{random, settings, Context, Error};
+
+const QUESTION_SECONDS: u64 = 30;
+const LEADERBOARD_SIZE: usize = 10;
+/// Questions generated before giving up, as some Pokemon lack the data a question needs.
+const MAX_ATTEMPTS: usize = 10;
+/// Discord caps button labels at 80 characters.
+const MAX_LABEL_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum Difficulty {
+    #[name = "Easy"]
+    Easy,
+    #[name = "Medium"]
+    Medium,
+    #[name = "Hard"]
+    Hard,
+}
+
+impl Difficulty {
+    fn points(self) -> i64 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 2,
+            Difficulty::Hard => 3,
+        }
+    }
+
+    fn kinds(self) -> &'static [Kind] {
+        match self {
+            Difficulty::Easy => &[Kind::FullyEvolvedType],
+            Difficulty::Medium => &[Kind::Type, Kind::MissingAbility],
+            Difficulty::Hard => &[Kind::LevelUp, Kind::NightLocation],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    FullyEvolvedType,
+    Type,
+    MissingAbility,
+    LevelUp,
+    NightLocation,
+}
+
+struct Question {
+    text: String,
+    options: Vec<String>,
+    answer: usize,
+}
+
+/// Answer trivia questions about the server PokeDex.
+#[poise::command(slash_command, guild_only, subcommands("start", "leaderboard"), subcommand_required)]
+pub async fn trivia(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Ask a question in this channel. The first to press the right answer gets the points.
+#[poise::command(slash_command, guild_only)]
+pub async fn start(
+    ctx: Context<'_>,
+    #[description = "How obscure the question is (defaults to Medium)."] difficulty: Option<Difficulty>,
+    #[description = "Game dataset to ask about."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let difficulty = difficulty.unwrap_or(Difficulty::Medium);
+    let Some(question) = generate(&dataset, difficulty)? else {
+        return reply_error(ctx, "Could not come up with a question from this dataset.".to_owned()).await;
+    };
+    let points = difficulty.points();
+    let ctx_id = ctx.id();
+    let title = format!("Trivia ({}, {} point{})", difficulty.name(), points, if points == 1 { "" } else { "s" });
+    let embed = |footer: &str| {
+        serenity::CreateEmbed::new()
+            .title(&title)
+            .description(&question.text)
+            .footer(serenity::CreateEmbedFooter::new(footer))
+    };
+    // Once over, the buttons stay to show the answer but can no longer be pressed.
+    let buttons = |over: bool| {
+        vec![serenity::CreateActionRow::Buttons(
+            question
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| {
+                    serenity::CreateButton::new(format!("{}trivia{}", ctx_id, i))
+                        .label(option.chars().take(MAX_LABEL_LENGTH).collect::<String>())
+                        .style(if over && i == question.answer {
+                            serenity::ButtonStyle::Success
+                        } else {
+                            serenity::ButtonStyle::Secondary
+                        })
+                        .disabled(over)
+                })
+                .collect(),
+        )]
+    };
+    let handle = ctx
+        .send(
+            poise::CreateReply::default()
+                .embed(embed(&format!("Answer within {} seconds. One try each.", QUESTION_SECONDS)))
+                .components(buttons(false)),
+        )
+        .await?;
+
+    let guild_id = ctx.guild_id().unwrap();
+    let mut answered = HashSet::new();
+    let mut presses = serenity::ComponentInteractionCollector::new(ctx)
+        .filter(move |press| press.data.custom_id.starts_with(&format!("{}trivia", ctx_id)))
+        .timeout(Duration::from_secs(QUESTION_SECONDS))
+        .stream();
+    while let Some(press) = presses.next().await {
+        let Some(choice) = press
+            .data
+            .custom_id
+            .strip_prefix(&format!("{}trivia", ctx_id))
+            .and_then(|choice| choice.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        let content = if !answered.insert(press.user.id) {
+            "You already answered this one."
+        } else if choice != question.answer {
+            settings::record_trivia_answer(guild_id, press.user.id, 0)?;
+            "Wrong answer, better luck next time."
+        } else {
+            settings::record_trivia_answer(guild_id, press.user.id, points)?;
+            let footer = format!("{} got it right.", press.user.name);
+            press
+                .create_response(
+                    ctx.serenity_context(),
+                    serenity::CreateInteractionResponse::UpdateMessage(
+                        serenity::CreateInteractionResponseMessage::new()
+                            .embed(embed(&footer))
+                            .components(buttons(true)),
+                    ),
+                )
+                .await?;
+            return Ok(());
+        };
+        press
+            .create_response(
+                ctx.serenity_context(),
+                serenity::CreateInteractionResponse::Message(
+                    serenity::CreateInteractionResponseMessage::new().ephemeral(true).content(content),
+                ),
+            )
+            .await?;
+    }
+    handle
+        .edit(
+            ctx,
+            poise::CreateReply::default()
+                .embed(embed("Nobody got it right."))
+                .components(buttons(true)),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Show this server's best players.
+#[poise::command(slash_command, guild_only)]
+pub async fn leaderboard(ctx: Context<'_>) -> Result<(), Error> {
+    let scores = settings::get_trivia_leaderboard(ctx.guild_id().unwrap(), LEADERBOARD_SIZE)?;
+    if scores.is_empty() {
+        return reply_error(ctx, "Nobody has answered a question in this server yet, ask one with `/trivia start`.".to_owned()).await;
+    }
+    let lines = scores
+        .iter()
+        .enumerate()
+        .map(|(i, score)| {
+            format!(
+                "{}. <@{}>: {} point{}, {}/{} right",
+                i + 1,
+                score.user_id,
+                score.points,
+                if score.points == 1 { "" } else { "s" },
+                score.correct,
+                score.answered
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    ctx.send(
+        poise::CreateReply::default()
+            .ephemeral(true)
+            .embed(serenity::CreateEmbed::new().title("Trivia leaderboard").description(lines)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// A question of the difficulty, or None if the dataset lacks the data for one.
+fn generate(dataset: &Dataset, difficulty: Difficulty) -> Result<Option<Question>, rusqlite::Error> {
+    let kinds = difficulty.kinds();
+    for _ in 0..MAX_ATTEMPTS {
+        let question = match kinds[random_below(kinds.len())] {
+            Kind::FullyEvolvedType => type_question(dataset, &[Filter::FullyEvolved])?,
+            Kind::Type => type_question(dataset, &[])?,
+            Kind::MissingAbility => ability_question(dataset)?,
+            Kind::LevelUp => levelup_question(dataset)?,
+            Kind::NightLocation => location_question(dataset)?,
+        };
+        if question.is_some() {
+            return Ok(question);
+        }
+    }
+    Ok(None)
+}
+
+/// "What type is X?"
+fn type_question(dataset: &Dataset, filters: &[Filter]) -> Result<Option<Question>, rusqlite::Error> {
+    let Some(pokemon_id) = random::random_pokemon(dataset, filters)? else {
+        return Ok(None);
+    };
+    let p = dex::get_pokemon_by_id(dataset, &pokemon_id)?;
+    let Some(answer) = types(p.type1_name, p.type2_name) else {
+        return Ok(None);
+    };
+    let path = "./src/trivia/queries/type_combinations.sql";
+    let sql = read_query(path);
+    let _timer = crate::metrics::time_query(path);
+    let conn = dataset.open();
+    let mut stmt = conn.prepare(&sql)?;
+    let mut wrong: Vec<String> = Vec::new();
+    for combination in stmt.query_map([], |row| Ok(types(row.get(0)?, row.get(1)?)))? {
+        if let Some(combination) = combination? {
+            if combination != answer && !wrong.contains(&combination) {
+                wrong.push(combination);
+            }
+        }
+        if wrong.len() == 3 {
+            break;
+        }
+    }
+    Ok(question(format!("What type is {}?", p.name), answer, wrong))
+}
+
+/// "Which ability does X NOT have?"
+fn ability_question(dataset: &Dataset) -> Result<Option<Question>, rusqlite::Error> {
+    let Some(pokemon_id) = random::random_pokemon(dataset, &[])? else {
+        return Ok(None);
+    };
+    let p = dex::get_pokemon_by_id(dataset, &pokemon_id)?;
+    let mut abilities: Vec<String> = Vec::new();
+    for ability in dex::get_abilities(dataset, &p.id)? {
+        if !abilities.contains(&ability.name) {
+            abilities.push(ability.name);
+        }
+    }
+    abilities.truncate(3);
+    // With a single ability, the question would give it away.
+    if abilities.len() < 2 {
+        return Ok(None);
+    }
+    let path = "./src/trivia/queries/other_abilities.sql";
+    let sql = read_query(path);
+    let _timer = crate::metrics::time_query(path);
+    let conn = dataset.open();
+    let answer: Option<String> = conn
+        .query_row(&sql, [i64::from(p.id), 1], |row| row.get(0))
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+    Ok(answer.and_then(|answer| question(format!("Which ability does {} NOT have?", p.name), answer, abilities)))
+}
+
+/// "At what level does X learn Y?"
+fn levelup_question(dataset: &Dataset) -> Result<Option<Question>, rusqlite::Error> {
+    let Some(pokemon_id) = random::random_pokemon(dataset, &[])? else {
+        return Ok(None);
+    };
+    let p = dex::get_pokemon_by_id(dataset, &pokemon_id)?;
+    let path = "./src/trivia/queries/random_levelup_move.sql";
+    let sql = read_query(path);
+    let _timer = crate::metrics::time_query(path);
+    let conn = dataset.open();
+    let learned: Option<(String, i64)> = conn
+        .query_row(&sql, [p.id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+    let Some((move_name, level)) = learned else {
+        return Ok(None);
+    };
+    let mut candidates = level_candidates(level);
+    let mut wrong = Vec::new();
+    while wrong.len() < 3 && !candidates.is_empty() {
+        wrong.push(candidates.remove(random_below(candidates.len())).to_string());
+    }
+    let question = question(format!("At what level does {} learn {}?", p.name, move_name), level.to_string(), wrong);
+    // Levels read best in order.
+    Ok(question.map(|mut question| {
+        question.options.sort_by_key(|option| option.parse::<i64>().unwrap_or(0));
+        question.answer = question.options.iter().position(|option| *option == level.to_string()).unwrap_or(0);
+        question
+    }))
+}
+
+/// "Where can X be found at night?"
+fn location_question(dataset: &Dataset) -> Result<Option<Question>, rusqlite::Error> {
+    let path = "./src/trivia/queries/random_night_sighting.sql";
+    let sql = read_query(path);
+    let conn = dataset.open();
+    let sighting: Option<(u16, String)> = {
+        let _timer = crate::metrics::time_query(path);
+        conn.query_row(&sql, [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?
+    };
+    let Some((pokemon_id, answer)) = sighting else {
+        return Ok(None);
+    };
+    let p = dex::get_pokemon_by_id(dataset, &pokemon_id)?;
+    let path = "./src/trivia/queries/other_locations.sql";
+    let sql = read_query(path);
+    let _timer = crate::metrics::time_query(path);
+    let mut stmt = conn.prepare(&sql)?;
+    let wrong = stmt
+        .query_map([i64::from(p.id), 3], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(question(format!("Where can {} be found at night?", p.name), answer, wrong))
+}
+
+/// The question with the answer among the wrong options at a random place, if there are enough options.
+fn question(text: String, answer: String, mut options: Vec<String>) -> Option<Question> {
+    if options.len() < 2 {
+        return None;
+    }
+    let answer_index = random_below(options.len() + 1);
+    options.insert(answer_index, answer);
+    Some(Question {
+        text,
+        options,
+        answer: answer_index,
+    })
+}
+
+/// Wrong levels close enough to the right one to make it a guess, all of them reachable.
+fn level_candidates(level: i64) -> Vec<i64> {
+    [-12, -8, -4, 4, 8, 12]
+        .iter()
+        .map(|offset| level + offset)
+        .filter(|candidate| (2..=100).contains(candidate))
+        .collect()
+}
+
+/// Types as written in search results, e.g. "Fire/Flying". Monotypes have the same type twice in the database.
+fn types(type1: Option<String>, type2: Option<String>) -> Option<String> {
+    match (type1, type2) {
+        (Some(type1), Some(type2)) if type1 != type2 => Some(format!("{}/{}", type1, type2)),
+        (Some(type1), _) => Some(type1),
+        _ => None,
+    }
+}
+
+/// A random number below `n`, from the hasher keys std seeds randomly.
+fn random_below(n: usize) -> usize {
+    std::collections::hash_map::RandomState::new().build_hasher().finish() as usize % n.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_index_points_at_the_answer() {
+        for _ in 0..20 {
+            let options = vec!["Overgrow".to_owned(), "Blaze".to_owned(), "Torrent".to_owned()];
+            let question = question("Which?".to_owned(), "Chlorophyll".to_owned(), options).unwrap();
+            assert_eq!(question.options.len(), 4);
+            assert_eq!(question.options[question.answer], "Chlorophyll");
+        }
+    }
+
+    #[test]
+    fn too_few_options_make_no_question() {
+        assert!(question("Which?".to_owned(), "Chlorophyll".to_owned(), vec!["Blaze".to_owned()]).is_none());
+    }
+
+    #[test]
+    fn types_collapse_monotypes() {
+        let name = |name: &str| Some(name.to_owned());
+        assert_eq!(types(name("Fire"), name("Fire")), name("Fire"));
+        assert_eq!(types(name("Fire"), name("Flying")), name("Fire/Flying"));
+        assert_eq!(types(name("Fire"), None), name("Fire"));
+        assert_eq!(types(None, None), None);
+    }
+
+    #[test]
+    fn level_candidates_stay_within_reach() {
+        for level in 1..=100 {
+            let candidates = level_candidates(level);
+            assert!(candidates.iter().all(|candidate| (2..=100).contains(candidate) && *candidate != level));
+        }
+        assert_eq!(level_candidates(1), [5, 9, 13]);
+        assert_eq!(level_candidates(50), [38, 42, 46, 54, 58, 62]);
+        assert_eq!(level_candidates(98), [86, 90, 94]);
+    }
+}
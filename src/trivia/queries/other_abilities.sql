@@ -0,0 +1,20 @@
+-- Abilities some Pokemon has, but not this one.
+select
+    a.name
+from
+    abilities a
+where
+    exists (select 1 from pokemon_ability_relationships pa where pa.ability = a.id)
+    and a.name not in (
+        select a2.name
+        from
+            pokemon_ability_relationships pa
+        inner join
+            abilities a2 on (a2.id = pa.ability)
+        where
+            pa.pokemon = ?1
+    )
+order by
+    random()
+limit
+    ?2;
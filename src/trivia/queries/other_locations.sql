@@ -0,0 +1,24 @@
+-- Places with wild Pokemon where this one cannot be found at night.
+select distinct
+    l.name
+from
+    sightings s
+inner join
+    locations l on (l.id = s.location)
+where
+    l.name not in (
+        select l2.name
+        from
+            sightings s2
+        inner join
+            locations l2 on (l2.id = s2.location)
+        inner join
+            encounter_methods em on (em.id = s2.encounter_method)
+        where
+            s2.pokemon = ?1
+            and em.name = 'Night'
+    )
+order by
+    random()
+limit
+    ?2;
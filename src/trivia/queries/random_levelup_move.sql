@@ -0,0 +1,16 @@
+-- A move the Pokemon learns at a single level past 1, so there is one right answer.
+select
+    m.name,
+    l.level
+from
+    levelup_learnsets l
+inner join
+    moves m on (m.id = l.move)
+where
+    l.pokemon = ?1
+    and l.level > 1
+    and (select count(*) from levelup_learnsets o where o.pokemon = l.pokemon and o.move = l.move) = 1
+order by
+    random()
+limit
+    1;
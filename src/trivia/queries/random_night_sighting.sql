@@ -0,0 +1,15 @@
+select
+    s.pokemon,
+    l.name
+from
+    sightings s
+inner join
+    locations l on (l.id = s.location)
+inner join
+    encounter_methods em on (em.id = s.encounter_method)
+where
+    em.name = 'Night'
+order by
+    random()
+limit
+    1;
@@ -0,0 +1,13 @@
+select distinct
+    t.name as type1_name,
+    t2.name as type2_name
+from
+    pokemon p
+inner join
+    types t on (t.id = p.type1)
+left join
+    types t2 on (t2.id = p.type2)
+where
+    p.base_total > 0
+order by
+    random();
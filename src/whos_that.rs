@@ -0,0 +1,308 @@
+//! "Who's that Pokemon?": the bot posts a random Pokemon's silhouette, reveals a hint every so often,
+//! and the first player to type its name in the channel wins the round. Wins and streaks are kept per guild.
+
+use futures::StreamExt;
+use image::imageops;
+use image::{ImageFormat, Rgba, RgbaImage};
+use poise::serenity_prelude as serenity;
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use crate::datasets::{self, Dataset};
+use crate::dex::{self, reply_error, Pokemon};
+use crate::sprites::{self, Sprite, Variant};
+use crate::{card, random, settings, Context, Error};
+
+const FILE_NAME: &str = "silhouette.png";
+const DEFAULT_ROUND_SECONDS: u64 = 60;
+const LEADERBOARD_SIZE: usize = 10;
+/// Pokemon picked looking for one with a sprite, before settling for one without.
+const MAX_PICKS: usize = 5;
+const CANVAS_SIZE: u32 = 256;
+const CANVAS: Rgba<u8> = Rgba([214, 226, 240, 255]);
+const SILHOUETTE: Rgba<u8> = Rgba([20, 20, 24, 255]);
+
+/// Channels with a round going on.
+static ROUNDS: LazyLock<Mutex<HashSet<serenity::ChannelId>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// A channel's round, which ends when dropped.
+struct Round(serenity::ChannelId);
+
+impl Round {
+    /// None if the channel already has a round going on.
+    fn start(channel_id: serenity::ChannelId) -> Option<Round> {
+        ROUNDS.lock().unwrap().insert(channel_id).then_some(Round(channel_id))
+    }
+}
+
+impl Drop for Round {
+    fn drop(&mut self) {
+        ROUNDS.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Guess the Pokemon from its silhouette.
+#[poise::command(slash_command, guild_only, rename = "whosthat", subcommands("start", "leaderboard"), subcommand_required)]
+pub async fn whos_that(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Start a round in this channel. Type the Pokemon's name in the channel to answer.
+#[poise::command(slash_command, guild_only)]
+pub async fn start(
+    ctx: Context<'_>,
+    #[description = "Seconds before the answer is revealed (defaults to 60)."]
+    #[min = 20]
+    #[max = 300]
+    seconds: Option<u64>,
+    #[description = "Game dataset to pick the Pokemon from."]
+    #[autocomplete = "datasets::autocomplete_dataset"]
+    dataset: Option<String>,
+) -> Result<(), Error> {
+    let dataset = match datasets::resolve(ctx, dataset.as_deref()) {
+        Ok(dataset) => dataset,
+        Err(message) => return reply_error(ctx, message).await,
+    };
+    let Some(_round) = Round::start(ctx.channel_id()) else {
+        return reply_error(ctx, "A round is already going on in this channel.".to_owned()).await;
+    };
+    let Some((p, sprite)) = pick(&dataset)? else {
+        return reply_error(ctx, "There is no Pokemon to guess in this dataset.".to_owned()).await;
+    };
+    let silhouette = silhouette(&sprite.png().await)?;
+    let hints = hints(&dataset, &p)?;
+    let seconds = seconds.unwrap_or(DEFAULT_ROUND_SECONDS);
+    let timeout = Duration::from_secs(seconds);
+    // The last hint comes one interval before the answer.
+    let interval = timeout / (hints.len() as u32 + 1);
+    let embed = |shown: usize| {
+        serenity::CreateEmbed::new()
+            .title("Who's that Pokemon?")
+            .description(format!("Type its name in this channel. The answer is revealed after {} seconds.", seconds))
+            .image(format!("attachment://{}", FILE_NAME))
+            .fields(hints[..shown].iter().map(|(name, value)| (*name, value.clone(), true)))
+    };
+    let handle = ctx
+        .send(
+            poise::CreateReply::default()
+                .embed(embed(0))
+                .attachment(serenity::CreateAttachment::bytes(silhouette, FILE_NAME)),
+        )
+        .await?;
+
+    let started = tokio::time::Instant::now();
+    let mut messages = serenity::MessageCollector::new(ctx)
+        .channel_id(ctx.channel_id())
+        .filter(|message| !message.author.bot)
+        .stream();
+    let mut shown = 0;
+    let winner = loop {
+        let next = if shown < hints.len() { started + interval * (shown as u32 + 1) } else { started + timeout };
+        tokio::select! {
+            message = messages.next() => {
+                let Some(message) = message else {
+                    break None;
+                };
+                if is_answer(&dataset, &p, &message.content)? {
+                    break Some(message);
+                }
+            }
+            _ = tokio::time::sleep_until(next) => {
+                if shown == hints.len() {
+                    break None;
+                }
+                shown += 1;
+                handle.edit(ctx, poise::CreateReply::default().embed(embed(shown))).await?;
+            }
+        }
+    };
+
+    let guild_id = ctx.guild_id().unwrap();
+    let description = match &winner {
+        Some(message) => {
+            let score = settings::record_whos_that_win(guild_id, message.author.id)?;
+            format!(
+                "<@{}> got it! {} win{}, {} in a row.",
+                message.author.id,
+                score.wins,
+                if score.wins == 1 { "" } else { "s" },
+                score.streak
+            )
+        }
+        None => {
+            settings::end_whos_that_streaks(guild_id)?;
+            "Nobody got it this time.".to_owned()
+        }
+    };
+    let (sprite_url, attachment) = sprites::find(&dataset, p.sprite.as_deref(), Variant::Front).for_embed().await;
+    let reveal = poise::CreateReply::default().embed(
+        serenity::CreateEmbed::new()
+            .title(format!("It's #{} {}!", p.pokedex_id.unwrap_or(0), p.name))
+            .description(description)
+            .colour(dex::get_color_from_type(p.type1_name.as_deref().unwrap_or("")))
+            .thumbnail(sprite_url),
+    )
+    .attachment(attachment);
+    ctx.send(reveal).await?;
+    Ok(())
+}
+
+/// Show this server's best players.
+#[poise::command(slash_command, guild_only)]
+pub async fn leaderboard(ctx: Context<'_>) -> Result<(), Error> {
+    let scores = settings::get_whos_that_leaderboard(ctx.guild_id().unwrap(), LEADERBOARD_SIZE)?;
+    if scores.is_empty() {
+        return reply_error(ctx, "Nobody has won a round in this server yet, start one with `/whosthat start`.".to_owned()).await;
+    }
+    let lines = scores
+        .iter()
+        .enumerate()
+        .map(|(i, score)| {
+            format!(
+                "{}. <@{}>: {} win{}, best streak {}{}",
+                i + 1,
+                score.user_id,
+                score.wins,
+                if score.wins == 1 { "" } else { "s" },
+                score.best_streak,
+                if score.streak > 1 { format!(", on a streak of {}", score.streak) } else { String::new() }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    ctx.send(
+        poise::CreateReply::default()
+            .ephemeral(true)
+            .embed(serenity::CreateEmbed::new().title("Who's that Pokemon? leaderboard").description(lines)),
+    )
+    .await?;
+    Ok(())
+}
+
+/// A random Pokemon, preferably one whose sprite can be found.
+fn pick(dataset: &Dataset) -> Result<Option<(Pokemon, Sprite)>, rusqlite::Error> {
+    let mut picked = None;
+    for _ in 0..MAX_PICKS {
+        let Some(pokemon_id) = random::random_pokemon(dataset, &[])? else {
+            return Ok(None);
+        };
+        let p = dex::get_pokemon_by_id(dataset, &pokemon_id)?;
+        let sprite = sprites::find(dataset, p.sprite.as_deref(), Variant::Front);
+        let found = !matches!(sprite, Sprite::Missing);
+        picked = Some((p, sprite));
+        if found {
+            break;
+        }
+    }
+    Ok(picked)
+}
+
+/// Hints revealed one at a time during a round, from the vaguest to the most telling.
+fn hints(dataset: &Dataset, p: &Pokemon) -> Result<Vec<(&'static str, String)>, rusqlite::Error> {
+    let mut hints = Vec::new();
+    hints.extend(pair(&p.type1_name, &p.type2_name).map(|types| ("Types", types)));
+    hints.extend(pair(&p.egg_group1_name, &p.egg_group2_name).map(|egg_groups| ("Egg Groups", egg_groups)));
+    hints.extend(dex::get_abilities(dataset, &p.id)?.into_iter().next().map(|ability| ("An Ability", ability.name)));
+    hints.push(("BST", p.base_total.to_string()));
+    Ok(hints)
+}
+
+/// Both names, or one if they are the same, as monotypes store their type twice.
+fn pair(first: &Option<String>, second: &Option<String>) -> Option<String> {
+    match (first, second) {
+        (Some(first), Some(second)) if first != second => Some(format!("{}, {}", first, second)),
+        (Some(first), _) => Some(first.clone()),
+        _ => None,
+    }
+}
+
+/// Whether a message names the Pokemon, matched the way `/dex` matches typed names.
+fn is_answer(dataset: &Dataset, p: &Pokemon, content: &str) -> Result<bool, rusqlite::Error> {
+    let guess = content.trim();
+    if guess.eq_ignore_ascii_case(&p.name) {
+        return Ok(true);
+    }
+    // Wildcards would let a guess match several names.
+    if guess.is_empty() || guess.contains(['%', '_']) {
+        return Ok(false);
+    }
+    match dex::get_pokemon_by_name(dataset, guess) {
+        Ok(guessed) => Ok(guessed.id == p.id),
+        // Most messages in the channel are chatter, not names.
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// The sprite as a dark shape on a light background, which stands out in both Discord themes.
+fn silhouette(png: &[u8]) -> Result<Vec<u8>, image::ImageError> {
+    let mut sprite = card::prepare_sprite(png)?;
+    for pixel in sprite.pixels_mut() {
+        *pixel = if pixel[3] > 0 { SILHOUETTE } else { CANVAS };
+    }
+    let mut canvas = RgbaImage::from_pixel(CANVAS_SIZE, CANVAS_SIZE, CANVAS);
+    let x = (CANVAS_SIZE.saturating_sub(sprite.width())) / 2;
+    let y = (CANVAS_SIZE.saturating_sub(sprite.height())) / 2;
+    imageops::overlay(&mut canvas, &sprite, x as i64, y as i64);
+    let mut png = Vec::new();
+    canvas.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::Datasets;
+
+    fn datasets() -> Datasets {
+        let _ = crate::config::init(&[]);
+        Datasets::load().unwrap()
+    }
+
+    #[test]
+    fn guesses_that_name_no_pokemon_are_wrong() {
+        let datasets = datasets();
+        let dataset = datasets.default();
+        let p = dex::get_pokemon_by_name(&dataset, "Pikachu").unwrap();
+        assert!(is_answer(&dataset, &p, " pikachu ").unwrap());
+        assert!(!is_answer(&dataset, &p, "is it a bird?").unwrap());
+        assert!(!is_answer(&dataset, &p, "Bulbasaur").unwrap());
+        assert!(!is_answer(&dataset, &p, "Pika%").unwrap());
+    }
+
+    #[test]
+    fn pairs_collapse_monotypes() {
+        let name = |name: &str| Some(name.to_owned());
+        assert_eq!(pair(&name("Fire"), &name("Fire")), name("Fire"));
+        assert_eq!(pair(&name("Fire"), &name("Flying")), name("Fire, Flying"));
+        assert_eq!(pair(&name("Field"), &None), name("Field"));
+        assert_eq!(pair(&None, &None), None);
+    }
+
+    #[test]
+    fn hints_list_a_monotype_once() {
+        let datasets = datasets();
+        let dataset = datasets.default();
+        let p = dex::get_pokemon_by_name(&dataset, "Charmander").unwrap();
+        assert_eq!(p.type1, p.type2);
+        let hints = hints(&dataset, &p).unwrap();
+        assert_eq!(hints[0], ("Types", "Fire".to_owned()));
+    }
+
+    #[test]
+    fn silhouettes_fit_large_sprites_on_the_canvas() {
+        let mut sprite = RgbaImage::new(CANVAS_SIZE + 144, CANVAS_SIZE + 44);
+        for (x, y, pixel) in sprite.enumerate_pixels_mut() {
+            if (100..300).contains(&x) && (100..200).contains(&y) {
+                *pixel = Rgba([200, 40, 40, 255]);
+            }
+        }
+        let mut png = Vec::new();
+        sprite.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
+        let silhouette = image::load_from_memory(&silhouette(&png).unwrap()).unwrap().to_rgba8();
+        assert_eq!(silhouette.dimensions(), (CANVAS_SIZE, CANVAS_SIZE));
+        assert_eq!(*silhouette.get_pixel(0, 0), CANVAS);
+        assert_eq!(*silhouette.get_pixel(CANVAS_SIZE / 2, CANVAS_SIZE / 2), SILHOUETTE);
+    }
+}
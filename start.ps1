@@ -1,2 +0,0 @@
-$Env:DISCORD_TOKEN = Get-Content .\secret.txt -Raw
-cargo run